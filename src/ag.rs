@@ -1,74 +1,95 @@
 extern crate rand;
 
-use std::u64;
-use std::time::{Duration, Instant};
 use std::cmp::{min, max};
 use self::rand::Rng;
-use instancia::{Instancia, IdTarefa, Solucao, Sequencia};
+use instancia::{Instancia, IdTarefa, Solucao, Sequencia, Avaliador, Temporizacao,
+                verifica_instancia};
+use erro::{Erro, verifica_parametro};
+use solver::{Solver, Resultado, Criterios, Parametros, Incumbente, multi_start, num_threads,
+             semente_aleatoria};
+use objetivo::{Objetivo, AdiantamentoAtraso};
 
 type Populacao = Vec<Solucao>;
 
-#[derive(Clone, Copy)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug)]
 pub enum Cruzamento {
     OX,
     PMX,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub enum Mutacao {
     Swap,
     TwoOpt,
 }
 
-#[allow(dead_code)]
-pub fn solve(inst: &Instancia,
-             timeout: Duration, // 30s
-             max_iter: u64, // INF
-             pop_tam: usize, // 250, 500
-             xo_chance: f64, // 0.95, 0.99
-             cruz: Cruzamento, // metodo de cruzamento: OX, PMX
-             mutacao: Mutacao, // método de mutação: Swap, 2-opt
-             mut_chance: f64 /* 0.05 0.10 0.20 */)
-             -> (Solucao, u64, u64) {
-    let mut rng = rand::weak_rng();
-    let mut pop = populacao_inicial(&mut rng, inst, pop_tam);
+#[allow(clippy::too_many_arguments)]
+pub fn solve<R: Rng + Sized>(rng: &mut R,
+                             av: &Avaliador,
+                             criterios: &Criterios,
+                             pop_tam: usize, // 250, 500
+                             xo_chance: f64, // 0.95, 0.99
+                             cruz: Cruzamento, // metodo de cruzamento: OX, PMX
+                             mutacao: Mutacao, // método de mutação: Swap, 2-opt
+                             mut_chance: f64, // 0.05 0.10 0.20
+                             incumbente: &Incumbente)
+                             -> Resultado {
+    av.limita_avaliacoes(criterios.max_avaliacoes);
+    let mut pop = populacao_inicial(rng, av, pop_tam);
+    incumbente.atualiza(&pop[0]);
     let mut best_fo = pop[0].fo();
     let mut it = 0;
     let mut it_melhor = 0;
     let xo_num = ((xo_chance * pop_tam as f64) / 2.0).floor() as usize;
-    let t = Instant::now();
 
-    while it - it_melhor < max_iter && t.elapsed() < timeout {
+    let parada = loop {
+        if let Some(parada) = criterios.verifica(av, it, it_melhor, incumbente.fo()) {
+            break parada;
+        }
+
         let filhos;
         {
-            let pais = selecao(&mut rng, &pop, xo_num);
-            filhos = recombinacao(&mut rng, inst, pais, mut_chance, cruz, mutacao);
+            let pais = selecao(rng, &pop, xo_num);
+            filhos = recombinacao(rng, av, pais, mut_chance, cruz, mutacao);
         }
         pop = proxima_geracao(pop, filhos, pop_tam);
 
         if pop[0].fo() < best_fo {
             it_melhor = it;
             best_fo = pop[0].fo();
+            av.conta_melhoria();
+            incumbente.atualiza(&pop[0]);
         }
         it += 1;
-    }
+    };
 
-    (pop.swap_remove(0), it_melhor, it)
+    Resultado {
+        solucao: pop.swap_remove(0),
+        it_alvo: it_melhor,
+        num_iter: it,
+        estatisticas: av.estatisticas(),
+        parada,
+        semente: 0,
+    }
 }
 
-#[allow(dead_code)]
 fn gen_roleta(pop: &Populacao) -> Vec<f32> {
-    let total = pop.iter().map(|s| 1.0 / s.fo() as f32).sum::<f32>();
+    // Desloca as FOs quando há valores nulos ou negativos (ex.: Lmax)
+    let menor = pop.iter().map(Solucao::fo).min().unwrap_or(1);
+    let desloc = if menor <= 0 { 1 - menor } else { 0 };
+    let aptidao = |s: &Solucao| 1.0 / (s.fo() + desloc) as f32;
+
+    let total = pop.iter().map(&aptidao).sum::<f32>();
     pop.iter()
-        .scan(0.0, |state, prob| {
-            *state += prob.fo() as f32 / total;
+        .scan(0.0, |state, s| {
+            *state += aptidao(s) / total;
             Some(*state)
         })
         .collect()
 }
 
-#[allow(dead_code)]
-fn get_index_from_roleta<R: Rng + Sized>(mut rng: &mut R, roleta: &[f32]) -> usize {
+fn get_index_from_roleta<R: Rng + Sized>(rng: &mut R, roleta: &[f32]) -> usize {
     let x = rng.next_f32();
     for (i, &prob) in roleta.iter().enumerate() {
         if x <= prob {
@@ -78,8 +99,7 @@ fn get_index_from_roleta<R: Rng + Sized>(mut rng: &mut R, roleta: &[f32]) -> usi
     0
 }
 
-#[allow(dead_code)]
-fn seleciona_pais<'a, R: Rng + Sized>(mut rng: &mut R,
+fn seleciona_pais<'a, R: Rng + Sized>(rng: &mut R,
                                       pop: &'a Populacao,
                                       roleta: &[f32])
                                       -> (&'a Sequencia, &'a Sequencia) {
@@ -88,7 +108,6 @@ fn seleciona_pais<'a, R: Rng + Sized>(mut rng: &mut R,
     (pai1, pai2)
 }
 
-#[allow(dead_code)]
 fn selecao<'a, R: Rng + Sized>(rng: &mut R,
                                pop: &'a Populacao,
                                xo_num: usize)
@@ -99,55 +118,54 @@ fn selecao<'a, R: Rng + Sized>(rng: &mut R,
         .collect()
 }
 
-#[allow(dead_code)]
 fn proxima_geracao(atual: Populacao, mut filhos: Populacao, pop_tam: usize) -> Populacao {
     let mut proxima = atual;
     let begin = pop_tam - filhos.len();
 
-    for i in begin..pop_tam {
-        proxima[i] = filhos.pop().expect("Erro ao gerar próxima geração");
+    for individuo in &mut proxima[begin..pop_tam] {
+        *individuo = filhos.pop().expect("Erro ao gerar próxima geração");
     }
 
     proxima.sort_by_key(Solucao::fo);
     proxima
 }
 
-#[allow(dead_code)]
-fn populacao_inicial<R: Rng + Sized>(rng: &mut R, inst: &Instancia, pop_tam: usize) -> Populacao {
-    let mut pop: Vec<_> = (0..pop_tam).map(|_| individuo_aleatorio(rng, inst)).collect();
+// Sem orçamento de avaliações, a população fica menor que `pop_tam` (com pelo
+// menos um indivíduo), e a execução termina sem nenhuma geração
+fn populacao_inicial<R: Rng + Sized>(rng: &mut R, av: &Avaliador, pop_tam: usize) -> Populacao {
+    let mut pop = vec![individuo_aleatorio(rng, av)];
+    while pop.len() < pop_tam && !av.esgotado() {
+        pop.push(individuo_aleatorio(rng, av));
+    }
     pop.sort_by_key(Solucao::fo);
     pop
 }
 
-#[allow(dead_code)]
-fn individuo_aleatorio<R: Rng + Sized>(mut rng: &mut R, inst: &Instancia) -> Solucao {
-    Solucao::new(inst, sequencia_aleatoria(rng, inst))
+fn individuo_aleatorio<R: Rng + Sized>(rng: &mut R, av: &Avaliador) -> Solucao {
+    Solucao::new(av, sequencia_aleatoria(rng, av.inst()))
 }
 
-#[allow(dead_code)]
-fn sequencia_aleatoria<R: Rng + Sized>(mut rng: &mut R, inst: &Instancia) -> Sequencia {
+fn sequencia_aleatoria<R: Rng + Sized>(rng: &mut R, inst: &Instancia) -> Sequencia {
     let num_tarefas = inst.num_tarefas();
     let mut seq: Vec<_> = (0..num_tarefas).collect();
     rng.shuffle(seq.as_mut_slice());
     seq
 }
 
-#[allow(dead_code)]
-fn two_opt_aleatorio<R: Rng + Sized>(mut rng: &mut R, mut sequencia: Sequencia) -> Sequencia {
+fn two_opt_aleatorio<R: Rng + Sized>(rng: &mut R, mut sequencia: Sequencia) -> Sequencia {
     let (i, k) = gen_points(rng, sequencia.len());
     sequencia[i..k].reverse();
     sequencia
 }
 
-#[allow(dead_code)]
-fn gen_points<R: Rng + Sized>(mut rng: &mut R, num_tarefas: usize) -> (IdTarefa, IdTarefa) {
+fn gen_points<R: Rng + Sized>(rng: &mut R, num_tarefas: usize) -> (IdTarefa, IdTarefa) {
     let i = rng.gen::<IdTarefa>() % num_tarefas;
     let j = rng.gen::<IdTarefa>() % num_tarefas;
 
     (min(i, j), max(i, j))
 }
 
-fn pmx_crossover<R: Rng + Sized>(mut rng: &mut R, pai1: &Sequencia, pai2: &Sequencia) -> Sequencia {
+fn pmx_crossover<R: Rng + Sized>(rng: &mut R, pai1: &Sequencia, pai2: &Sequencia) -> Sequencia {
     let num_tarefas = pai1.len();
 
     let mut genes = pai1.clone();
@@ -169,8 +187,7 @@ fn pmx_crossover<R: Rng + Sized>(mut rng: &mut R, pai1: &Sequencia, pai2: &Seque
     genes
 }
 
-#[allow(dead_code)]
-fn ordered_crossover<R: Rng + Sized>(mut rng: &mut R,
+fn ordered_crossover<R: Rng + Sized>(rng: &mut R,
                                      pai1: &Sequencia,
                                      pai2: &Sequencia)
                                      -> Sequencia {
@@ -203,7 +220,7 @@ fn ordered_crossover<R: Rng + Sized>(mut rng: &mut R,
     filho.into_iter().map(|o| o.expect("Erro no OX")).collect()
 }
 
-fn cruzamento<R: Rng + Sized>(mut rng: &mut R,
+fn cruzamento<R: Rng + Sized>(rng: &mut R,
                               cruz: Cruzamento,
                               pais: Vec<(&Sequencia, &Sequencia)>)
                               -> Vec<Sequencia> {
@@ -221,9 +238,8 @@ fn cruzamento<R: Rng + Sized>(mut rng: &mut R,
     filhos
 }
 
-#[allow(dead_code)]
-fn recombinacao<R: Rng + Sized>(mut rng: &mut R,
-                                inst: &Instancia,
+fn recombinacao<R: Rng + Sized>(rng: &mut R,
+                                av: &Avaliador,
                                 pais: Vec<(&Sequencia, &Sequencia)>,
                                 mut_chance: f64,
                                 cruz: Cruzamento,
@@ -232,19 +248,18 @@ fn recombinacao<R: Rng + Sized>(mut rng: &mut R,
     cruzamento(rng, cruz, pais)
         .into_iter()
         .map(|seq| mutacao(rng, seq, mut_chance, metodo_mut))
-        .map(|seq| Solucao::new(inst, seq))
+        .take_while(|_| !av.esgotado())
+        .map(|seq| Solucao::new(av, seq))
         .collect()
 }
 
-#[allow(dead_code)]
-fn swap_vertices<R: Rng + Sized>(mut rng: &mut R, mut sequencia: Sequencia) -> Sequencia {
+fn swap_vertices<R: Rng + Sized>(rng: &mut R, mut sequencia: Sequencia) -> Sequencia {
     let (i, j) = gen_points(rng, sequencia.len());
     sequencia.swap(i, j);
     sequencia
 }
 
-#[allow(dead_code)]
-fn mutacao<R: Rng + Sized>(mut rng: &mut R,
+fn mutacao<R: Rng + Sized>(rng: &mut R,
                            sequencia: Sequencia,
                            mut_chance: f64,
                            metodo_mut: Mutacao)
@@ -262,80 +277,121 @@ fn mutacao<R: Rng + Sized>(mut rng: &mut R,
 
 pub struct Ag<'a> {
     inst: &'a Instancia,
-    timeout: u64,
-    max_iter: u64,
+    criterios: Criterios,
+    threads: usize,
     pop_tam: usize,
     xo_chance: f64,
     mut_chance: f64,
     cruz: Cruzamento,
     mutacao: Mutacao,
+    objetivo: &'a dyn Objetivo,
+    temporizacao: Temporizacao,
+    semente: Option<u64>,
 }
 impl<'a> Ag<'a> {
-    #[allow(dead_code)]
-    pub fn new(inst: &Instancia) -> Ag {
+    pub fn new(inst: &'a Instancia) -> Ag<'a> {
         Ag {
-            inst: inst,
-            timeout: u64::MAX,
-            max_iter: 1000,
+            inst,
+            criterios: Criterios {
+                max_iter: Some(1000),
+                ..Criterios::default()
+            },
+            threads: 1,
             pop_tam: 200,
             xo_chance: 0.8,
             mut_chance: 0.1,
             cruz: Cruzamento::PMX,
             mutacao: Mutacao::Swap,
+            objetivo: &AdiantamentoAtraso,
+            temporizacao: Temporizacao::Semiativa,
+            semente: None,
         }
     }
 
-    #[allow(dead_code)]
-    pub fn solve(&self) -> (Solucao, u64, u64) {
-        solve(self.inst,
-              Duration::from_secs(self.timeout),
-              self.max_iter,
-              self.pop_tam,
-              self.xo_chance,
-              self.cruz,
-              self.mutacao,
-              self.mut_chance)
-    }
-
-    #[allow(dead_code)]
-    pub fn timeout(&mut self, timeout: u64) -> &mut Ag<'a> {
-        self.timeout = timeout;
-        self
-    }
-
-    #[allow(dead_code)]
-    pub fn max_iter(&mut self, max_iter: u64) -> &mut Ag<'a> {
-        self.max_iter = max_iter;
-        self
-    }
+    metodos_criterios!();
 
-    #[allow(dead_code)]
     pub fn pop_tam(&mut self, pop_tam: usize) -> &mut Ag<'a> {
         self.pop_tam = pop_tam;
         self
     }
 
-    #[allow(dead_code)]
     pub fn xo_chance(&mut self, xo_chance: f64) -> &mut Ag<'a> {
         self.xo_chance = xo_chance;
         self
     }
 
-    #[allow(dead_code)]
     pub fn mut_chance(&mut self, mut_chance: f64) -> &mut Ag<'a> {
         self.mut_chance = mut_chance;
         self
     }
 
-    #[allow(dead_code)]
     pub fn cruz(&mut self, cruz: Cruzamento) -> &mut Ag<'a> {
         self.cruz = cruz;
         self
     }
 
-    #[allow(dead_code)]
     pub fn mutacao(&mut self, mutacao: Mutacao) -> &mut Ag<'a> {
         self.mutacao = mutacao;
         self
     }
+    pub fn objetivo(&mut self, objetivo: &'a dyn Objetivo) -> &mut Ag<'a> {
+        self.objetivo = objetivo;
+        self
+    }
+
+    // Número de threads da execução paralela; 0 usa todos os núcleos
+    pub fn threads(&mut self, threads: usize) -> &mut Ag<'a> {
+        self.threads = threads;
+        self
+    }
+
+    pub fn semente(&mut self, semente: u64) -> &mut Ag<'a> {
+        self.semente = Some(semente);
+        self
+    }
+
+    pub fn temporizacao(&mut self, temporizacao: Temporizacao) -> &mut Ag<'a> {
+        self.temporizacao = temporizacao;
+        self
+    }
+}
+
+impl<'a> Solver for Ag<'a> {
+    fn nome(&self) -> &'static str {
+        "AG"
+    }
+
+    fn parametros(&self) -> Parametros {
+        let mut parametros = vec![("pop_tam", self.pop_tam.to_string()),
+                                  ("xo_chance", self.xo_chance.to_string()),
+                                  ("cruz", format!("{:?}", self.cruz)),
+                                  ("mutacao", format!("{:?}", self.mutacao)),
+                                  ("mut_chance", self.mut_chance.to_string()),
+                                  ("threads", self.threads.to_string())];
+        parametros.extend(self.criterios.parametros());
+        parametros
+    }
+
+    fn solve(&self) -> Result<Resultado, Erro> {
+        let probabilidade = |p: f64| (0.0..=1.0).contains(&p);
+        verifica_instancia(self.inst)?;
+        verifica_parametro("pop_tam", self.pop_tam, self.pop_tam >= 2)?;
+        verifica_parametro("xo_chance", self.xo_chance, probabilidade(self.xo_chance))?;
+        verifica_parametro("mut_chance", self.mut_chance, probabilidade(self.mut_chance))?;
+
+        let threads = num_threads(self.threads);
+        let criterios = self.criterios.divide(threads as u64);
+        let semente = self.semente.unwrap_or_else(semente_aleatoria);
+        Ok(multi_start(threads, semente, |rng, incumbente| {
+            solve(rng,
+                  &Avaliador::new(self.inst, self.objetivo, self.temporizacao),
+                  &criterios,
+                  self.pop_tam,
+                  self.xo_chance,
+                  self.cruz,
+                  self.mutacao,
+                  self.mut_chance,
+                  incumbente)
+        }))
+    }
 }
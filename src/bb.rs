@@ -0,0 +1,415 @@
+use std::cmp::{min, max};
+use std::time::Duration;
+use erro::Erro;
+use grasp::{self, Construcao};
+use instancia::{Instancia, IdTarefa, Solucao, Sequencia, Avaliador, Temporizacao,
+                verifica_instancia, neh};
+use solver::{Solver, Resultado, Criterios, Parametros, Parada, Incumbente, semente_aleatoria,
+             gera_rng};
+use objetivo::{Objetivo, AdiantamentoAtraso};
+
+// Branch-and-bound exato para a soma ponderada de adiantamentos e atrasos
+// sem ociosidade inserida. A sequência é construída do início para o fim, em
+// profundidade, e a solução do GRASP é o limite superior inicial. O GRASP
+// usa o mesmo avaliador da árvore, então o tempo limite e o orçamento de
+// avaliações valem para os dois juntos.
+
+// Até este número de tarefas, o limite superior inicial vem do NEH: a árvore
+// é pequena demais para justificar a execução do GRASP
+const MAX_TAREFAS_NEH: usize = 3;
+
+// Resultado do branch-and-bound, com o limite inferior provado
+pub struct ResultadoExato {
+    pub resultado: Resultado,
+    pub limite_inferior: i32,
+}
+
+impl ResultadoExato {
+    pub fn otimo(&self) -> bool {
+        self.resultado.solucao.fo() <= self.limite_inferior
+    }
+
+    // Distância relativa entre a melhor solução e o limite inferior
+    pub fn gap(&self) -> f64 {
+        let fo = self.resultado.solucao.fo();
+        if fo <= self.limite_inferior {
+            0.0
+        } else {
+            (fo - self.limite_inferior) as f64 / fo as f64
+        }
+    }
+}
+
+// Sequência parcial e o instante em que ela termina
+struct No {
+    sequencia: Sequencia,
+    livre: Vec<bool>,
+    tempo: i32,
+    custo: i32,
+    // Custo da sequência parcial mais o limite inferior das tarefas livres
+    limite: i32,
+}
+
+struct Limites<'a> {
+    inst: &'a Instancia,
+    // Tarefas por duração crescente e por entrega crescente
+    spt: Sequencia,
+    edd: Sequencia,
+}
+
+impl<'a> Limites<'a> {
+    fn new(inst: &'a Instancia) -> Limites<'a> {
+        let mut spt: Sequencia = (0..inst.num_tarefas()).collect();
+        let mut edd = spt.clone();
+        spt.sort_by_key(|&t| (inst.tarefa(t).duracao(), t));
+        edd.sort_by_key(|&t| (inst.tarefa(t).entrega(), t));
+        Limites { inst, spt, edd }
+    }
+
+    // Limite inferior do custo das tarefas livres sequenciadas a partir de
+    // `tempo` sem ociosidade. Os i-ésimos menores instantes de finalização
+    // são pelo menos os da ordem SPT e no máximo os da ordem LPT, e associá-
+    // los às entregas ordenadas minimiza os atrasos e os adiantamentos.
+    fn restante(&self, tempo: i32, livre: &[bool]) -> i32 {
+        let tarefa = |t: IdTarefa| self.inst.tarefa(t);
+        let entregas: Vec<_> = self.edd.iter().filter(|&&t| livre[t]).map(|&t| tarefa(t).entrega()).collect();
+        if entregas.is_empty() {
+            return 0;
+        }
+
+        let mut peso_atraso = i32::MAX;
+        let mut peso_adiantamento = i32::MAX;
+        let mut atraso = 0;
+        let mut fim = tempo;
+        for (k, &t) in self.spt.iter().filter(|&&t| livre[t]).enumerate() {
+            fim += tarefa(t).duracao();
+            atraso += max(0, fim - entregas[k]);
+            peso_atraso = min(peso_atraso, tarefa(t).peso_atraso());
+            peso_adiantamento = min(peso_adiantamento, tarefa(t).peso_adiantamento());
+        }
+
+        let mut adiantamento = 0;
+        let mut fim = tempo;
+        for (k, &t) in self.spt.iter().rev().filter(|&&t| livre[t]).enumerate() {
+            fim += tarefa(t).duracao();
+            adiantamento += max(0, entregas[k] - fim);
+        }
+
+        peso_atraso * atraso + peso_adiantamento * adiantamento
+    }
+}
+
+pub fn solve(av: &Avaliador, criterios: &Criterios, inicial: Solucao) -> ResultadoExato {
+    let inst = av.inst();
+    let n = inst.num_tarefas();
+    let limites = Limites::new(inst);
+    let custo = |t: IdTarefa, fim: i32| av.objetivo().custo(inst.tarefa(t), fim);
+    let duracao = |t: IdTarefa| inst.tarefa(t).duracao();
+
+    av.limita_avaliacoes(criterios.max_avaliacoes);
+    let mut melhor = inicial;
+    let livre = vec![true; n];
+    let mut pilha = vec![No {
+                             sequencia: Vec::with_capacity(n),
+                             limite: limites.restante(0, &livre),
+                             livre,
+                             tempo: 0,
+                             custo: 0,
+                         }];
+
+    let mut it = 0;
+    let mut it_alvo = 0;
+    let mut parada = Parada::Otimo;
+
+    while let Some(no) = pilha.pop() {
+        if no.limite >= melhor.fo() {
+            continue;
+        }
+        if let Some(p) = criterios.verifica(av, it, it_alvo, melhor.fo()) {
+            pilha.push(no);
+            parada = p;
+            break;
+        }
+        it += 1;
+
+        let mut filhos = vec![];
+        let mut esgotado = false;
+        for j in (0..n).filter(|&j| no.livre[j]) {
+            if av.esgotado() {
+                esgotado = true;
+                break;
+            }
+
+            // Dominância entre adjacentes: sem ociosidade e sem setup, trocar
+            // a última tarefa `i` com `j` não muda o fim do par, então basta
+            // manter a ordem de menor custo (e, no empate, de menor índice)
+            if let Some(&i) = no.sequencia.last() {
+                let inicio = no.tempo - duracao(i);
+                let ij = custo(i, no.tempo) + custo(j, no.tempo + duracao(j));
+                let ji = custo(j, inicio + duracao(j)) + custo(i, no.tempo + duracao(j));
+                if ji < ij || (ji == ij && j < i) {
+                    continue;
+                }
+            }
+
+            let tempo = no.tempo + duracao(j);
+            let mut filho = No {
+                sequencia: no.sequencia.clone(),
+                livre: no.livre.clone(),
+                tempo,
+                custo: no.custo + custo(j, tempo),
+                limite: 0,
+            };
+            filho.sequencia.push(j);
+            filho.livre[j] = false;
+            filho.limite = filho.custo + limites.restante(tempo, &filho.livre);
+            av.conta_avaliacao();
+
+            if filho.limite >= melhor.fo() {
+                continue;
+            }
+            if filho.sequencia.len() == n {
+                melhor = Solucao::com_fo(filho.sequencia, filho.custo);
+                it_alvo = it;
+                av.conta_melhoria();
+            } else {
+                filhos.push(filho);
+            }
+        }
+
+        if esgotado {
+            // Sem orçamento, o nó volta inteiro para a pilha e os filhos
+            // gerados são descartados, para manter o limite inferior válido
+            pilha.push(no);
+            parada = Parada::Avaliacoes;
+            break;
+        }
+
+        // Os filhos de menor limite são explorados primeiro
+        filhos.sort_by_key(|f| -f.limite);
+        pilha.extend(filhos);
+    }
+
+    let limite_inferior = pilha.iter().map(|no| no.limite).fold(melhor.fo(), min);
+    ResultadoExato {
+        resultado: Resultado {
+            solucao: melhor,
+            it_alvo,
+            num_iter: it,
+            estatisticas: av.estatisticas(),
+            parada,
+            semente: 0,
+        },
+        limite_inferior,
+    }
+}
+
+pub struct BranchBound<'a> {
+    inst: &'a Instancia,
+    criterios: Criterios,
+    // Tempo do GRASP que fornece o limite superior inicial
+    tempo_grasp: Duration,
+    objetivo: &'a dyn Objetivo,
+    temporizacao: Temporizacao,
+    semente: Option<u64>,
+}
+
+impl<'a> BranchBound<'a> {
+    pub fn new(inst: &'a Instancia) -> BranchBound<'a> {
+        BranchBound {
+            inst,
+            criterios: Criterios {
+                tempo: Some(Duration::from_secs(60)),
+                ..Criterios::default()
+            },
+            tempo_grasp: Duration::from_secs(1),
+            objetivo: &AdiantamentoAtraso,
+            temporizacao: Temporizacao::Semiativa,
+            semente: None,
+        }
+    }
+
+    metodos_criterios!(tempo);
+
+    pub fn timeout_grasp_ms(&mut self, timeout: u64) -> &mut BranchBound<'a> {
+        self.tempo_grasp = Duration::from_millis(timeout);
+        self
+    }
+
+    // Só o objetivo et é suportado; os demais são rejeitados por `solve`
+    pub fn objetivo(&mut self, objetivo: &'a dyn Objetivo) -> &mut BranchBound<'a> {
+        self.objetivo = objetivo;
+        self
+    }
+
+    // Só a temporização semiativa é suportada
+    pub fn temporizacao(&mut self, temporizacao: Temporizacao) -> &mut BranchBound<'a> {
+        self.temporizacao = temporizacao;
+        self
+    }
+
+    // Semente do GRASP inicial
+    pub fn semente(&mut self, semente: u64) -> &mut BranchBound<'a> {
+        self.semente = Some(semente);
+        self
+    }
+
+    fn verifica(&self) -> Result<(), Erro> {
+        verifica_instancia(self.inst)?;
+
+        let nao_suporta = |motivo: &str| {
+            Err(Erro::NaoSuportado {
+                algoritmo: self.nome(),
+                motivo: motivo.to_string(),
+            })
+        };
+        if self.objetivo.nome() != AdiantamentoAtraso.nome() {
+            return nao_suporta(&format!("o objetivo {}", self.objetivo.nome()));
+        }
+        if self.temporizacao != Temporizacao::Semiativa {
+            return nao_suporta("a temporização ótima");
+        }
+        if self.inst.tem_setup() {
+            return nao_suporta("tempos de setup");
+        }
+        if (0..self.inst.num_tarefas()).any(|t| self.inst.tarefa(t).liberacao() > 0) {
+            return nao_suporta("datas de liberação");
+        }
+        Ok(())
+    }
+
+    // Como `solve`, mas também devolve o limite inferior, para calcular o gap
+    // quando a otimalidade não é provada dentro dos critérios de parada
+    pub fn resolve(&self) -> Result<ResultadoExato, Erro> {
+        self.verifica()?;
+
+        let av = Avaliador::new(self.inst, self.objetivo, self.temporizacao);
+        let semente = self.semente.unwrap_or_else(semente_aleatoria);
+        let inicial = if self.inst.num_tarefas() <= MAX_TAREFAS_NEH {
+            neh(&av)
+        } else {
+            self.limite_superior(&av, semente)
+        };
+
+        let mut res = solve(&av, &self.criterios, inicial);
+        res.resultado.semente = semente;
+        Ok(res)
+    }
+
+    // Solução do GRASP, limitado a `tempo_grasp` e aos critérios de tempo e
+    // de avaliações do branch-and-bound
+    fn limite_superior(&self, av: &Avaliador, semente: u64) -> Solucao {
+        let tempo = self.criterios.tempo.map_or(self.tempo_grasp, |t| min(t, self.tempo_grasp));
+        let criterios = Criterios {
+            tempo: Some(tempo),
+            max_avaliacoes: self.criterios.max_avaliacoes,
+            ..Criterios::default()
+        };
+        let res = grasp::solve(&mut gera_rng(semente),
+                               av,
+                               grasp::ALFA_INICIAL,
+                               Construcao::Neh,
+                               &criterios,
+                               grasp::NUM_VIZINHOS,
+                               &Incumbente::new());
+        if res.num_iter > 0 {
+            res.solucao
+        } else {
+            // Orçamento esgotado antes da primeira iteração do GRASP
+            Solucao::new(av, (0..self.inst.num_tarefas()).collect())
+        }
+    }
+}
+
+impl<'a> Solver for BranchBound<'a> {
+    fn nome(&self) -> &'static str {
+        "BranchBound"
+    }
+
+    fn parametros(&self) -> Parametros {
+        let mut parametros = vec![("tempo_grasp_ms", self.tempo_grasp.as_millis().to_string())];
+        parametros.extend(self.criterios.parametros());
+        parametros
+    }
+
+    fn solve(&self) -> Result<Resultado, Erro> {
+        self.resolve().map(|res| res.resultado)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use instancia::Tarefa;
+
+    // FO ótima por enumeração de todas as permutações
+    fn forca_bruta(av: &Avaliador) -> i32 {
+        fn permuta(av: &Avaliador, seq: &mut Sequencia, k: usize, melhor: &mut i32) {
+            if k == seq.len() {
+                *melhor = min(*melhor, Solucao::new(av, seq.clone()).fo());
+                return;
+            }
+            for i in k..seq.len() {
+                seq.swap(k, i);
+                permuta(av, seq, k + 1, melhor);
+                seq.swap(k, i);
+            }
+        }
+
+        let mut seq: Sequencia = (0..av.inst().num_tarefas()).collect();
+        let mut melhor = i32::MAX;
+        permuta(av, &mut seq, 0, &mut melhor);
+        melhor
+    }
+
+    #[test]
+    fn instancias_minimas() {
+        let tarefas = [(4, 3, 2, 1), (2, 9, 1, 3), (3, 5, 1, 2)];
+        for n in 1..4 {
+            let inst = Instancia::new(tarefas[..n]
+                .iter()
+                .map(|&(p, d, a, b)| Tarefa::com_pesos(p, d, a, b))
+                .collect());
+            let res = BranchBound::new(&inst).semente(n as u64).resolve().unwrap();
+            let av = Avaliador::new(&inst, &AdiantamentoAtraso, Temporizacao::Semiativa);
+
+            assert!(res.otimo());
+            assert_eq!(res.resultado.solucao.sequencia().len(), n);
+            assert_eq!(res.resultado.solucao.fo(), forca_bruta(&av));
+        }
+    }
+
+    #[test]
+    fn respeita_max_avaliacoes() {
+        let inst = Instancia::new(vec![Tarefa::com_pesos(4, 10, 2, 1),
+                                       Tarefa::com_pesos(2, 3, 1, 3),
+                                       Tarefa::com_pesos(3, 14, 1, 2),
+                                       Tarefa::com_pesos(5, 8, 3, 1),
+                                       Tarefa::com_pesos(1, 6, 2, 4),
+                                       Tarefa::com_pesos(6, 20, 1, 1),
+                                       Tarefa::com_pesos(2, 12, 4, 2)]);
+        let av = Avaliador::new(&inst, &AdiantamentoAtraso, Temporizacao::Semiativa);
+        let otimo = forca_bruta(&av);
+
+        // Sem tempo para o GRASP, o orçamento é gasto na árvore
+        for &tempo_grasp in &[0, 1000] {
+            for &max_avaliacoes in &[0, 1, 10, 50, 200] {
+                let res = BranchBound::new(&inst)
+                    .semente(1)
+                    .timeout_grasp_ms(tempo_grasp)
+                    .criterios(&Criterios {
+                        max_avaliacoes: Some(max_avaliacoes),
+                        ..Criterios::default()
+                    })
+                    .resolve()
+                    .unwrap();
+
+                // Uma construção interrompida ainda é avaliada uma vez
+                assert!(res.resultado.estatisticas.avaliacoes <= max_avaliacoes + 1);
+                assert!(res.resultado.parada == Parada::Avaliacoes || res.otimo());
+                assert_eq!(res.resultado.solucao.sequencia().len(), inst.num_tarefas());
+                assert!(res.limite_inferior <= otimo && otimo <= res.resultado.solucao.fo());
+            }
+        }
+    }
+}
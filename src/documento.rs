@@ -0,0 +1,340 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
+use erro::Erro;
+use instancia::{Instancia, Tarefa, IdTarefa, Avaliador, Temporizacao};
+use solver::{Solver, Resultado};
+
+// Formatos estruturados (JSON e TOML) de instâncias e soluções. O formato é
+// escolhido pela extensão do arquivo.
+
+fn um() -> i32 {
+    1
+}
+
+fn e_um(valor: &i32) -> bool {
+    *valor == 1
+}
+
+fn e_zero(valor: &i32) -> bool {
+    *valor == 0
+}
+
+// Os pesos valem 1 e a liberação vale 0 quando omitidos
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TarefaDoc {
+    pub id: IdTarefa,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nome: Option<String>,
+    pub duracao: i32,
+    pub entrega: i32,
+    #[serde(default = "um", skip_serializing_if = "e_um")]
+    pub peso_adiantamento: i32,
+    #[serde(default = "um", skip_serializing_if = "e_um")]
+    pub peso_atraso: i32,
+    #[serde(default, skip_serializing_if = "e_zero")]
+    pub liberacao: i32,
+}
+
+// Os ids das tarefas são as suas posições na instância, de 0 a n - 1, mas
+// podem aparecer em qualquer ordem. `setup[i][j]` é o setup entre as tarefas
+// de ids i e j.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InstanciaDoc {
+    pub tarefas: Vec<TarefaDoc>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub setup: Vec<Vec<i32>>,
+}
+
+impl InstanciaDoc {
+    pub fn from_instancia(inst: &Instancia) -> InstanciaDoc {
+        let n = inst.num_tarefas();
+        let tarefas = (0..n)
+            .map(|id| {
+                let t = inst.tarefa(id);
+                TarefaDoc {
+                    id,
+                    nome: t.nome().map(|n| n.to_string()),
+                    duracao: t.duracao(),
+                    entrega: t.entrega(),
+                    peso_adiantamento: t.peso_adiantamento(),
+                    peso_atraso: t.peso_atraso(),
+                    liberacao: t.liberacao(),
+                }
+            })
+            .collect();
+        let setup = if inst.tem_setup() {
+            (0..n).map(|i| (0..n).map(|j| inst.setup(Some(i), j)).collect()).collect()
+        } else {
+            vec![]
+        };
+
+        InstanciaDoc { tarefas, setup }
+    }
+
+    // Instância descrita pelo documento; `arquivo` só é usado nos erros
+    pub fn instancia(self, arquivo: &str) -> Result<Instancia, Erro> {
+        let n = self.tarefas.len();
+        let mut tarefas: Vec<Option<Tarefa>> = (0..n).map(|_| None).collect();
+
+        for t in self.tarefas {
+            if t.id >= n {
+                return Err(Erro::documento(arquivo,
+                                           format!("id {} fora do intervalo [0, {})", t.id, n)));
+            }
+            if tarefas[t.id].is_some() {
+                return Err(Erro::documento(arquivo, format!("id {} repetido", t.id)));
+            }
+            if t.duracao < 0 {
+                return Err(Erro::documento(arquivo,
+                                           format!("tarefa {}: duração negativa: {}",
+                                                   t.id,
+                                                   t.duracao)));
+            }
+
+            let mut tarefa = Tarefa::completa(t.duracao,
+                                              t.entrega,
+                                              t.peso_adiantamento,
+                                              t.peso_atraso,
+                                              t.liberacao);
+            if let Some(nome) = t.nome {
+                tarefa = tarefa.com_nome(nome);
+            }
+            tarefas[t.id] = Some(tarefa);
+        }
+
+        let tarefas = tarefas.into_iter().map(Option::unwrap).collect();
+        Instancia::com_setup(tarefas, self.setup).map_err(|e| Erro::documento(arquivo, e))
+    }
+}
+
+// Tempos de uma tarefa na solução
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AgendamentoDoc {
+    pub id: IdTarefa,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nome: Option<String>,
+    pub inicio: i32,
+    pub fim: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SolucaoDoc {
+    pub algoritmo: String,
+    pub objetivo: String,
+    pub temporizacao: String,
+    // Texto, porque nem TOML nem JavaScript representam todo u64
+    pub semente: String,
+    pub fo: i32,
+    pub sequencia: Vec<IdTarefa>,
+    // Tarefas na ordem da sequência
+    pub tarefas: Vec<AgendamentoDoc>,
+    pub parametros: BTreeMap<String, String>,
+}
+
+impl SolucaoDoc {
+    pub fn new(solver: &dyn Solver, av: &Avaliador, res: &Resultado) -> SolucaoDoc {
+        let inst = av.inst();
+        let sequencia = res.solucao.sequencia();
+        let fim = av.finalizacao(sequencia);
+        let tarefas = sequencia.iter()
+            .map(|&id| {
+                let t = inst.tarefa(id);
+                AgendamentoDoc {
+                    id,
+                    nome: t.nome().map(|n| n.to_string()),
+                    inicio: fim[id] - t.duracao(),
+                    fim: fim[id],
+                }
+            })
+            .collect();
+
+        SolucaoDoc {
+            algoritmo: solver.nome().to_string(),
+            objetivo: av.objetivo().nome().to_string(),
+            temporizacao: match av.temporizacao() {
+                Temporizacao::Semiativa => "semiativa",
+                Temporizacao::Otima => "otima",
+            }.to_string(),
+            semente: res.semente.to_string(),
+            fo: res.solucao.fo(),
+            sequencia: sequencia.clone(),
+            tarefas,
+            parametros: solver.parametros()
+                .into_iter()
+                .map(|(nome, valor)| (nome.to_string(), valor))
+                .collect(),
+        }
+    }
+}
+
+enum Extensao {
+    Json,
+    Toml,
+}
+
+fn extensao(arquivo: &str) -> Result<Extensao, Erro> {
+    match Path::new(arquivo).extension().and_then(|e| e.to_str()) {
+        Some("json") => Ok(Extensao::Json),
+        Some("toml") => Ok(Extensao::Toml),
+        _ => {
+            Err(Erro::ExtensaoDesconhecida {
+                arquivo: arquivo.to_string(),
+                aceitas: ".json ou .toml",
+            })
+        }
+    }
+}
+
+// Se o arquivo tem um formato estruturado
+pub fn estruturado(arquivo: &str) -> bool {
+    extensao(arquivo).is_ok()
+}
+
+// Lê um documento JSON ou TOML, de acordo com a extensão
+pub fn le<T: DeserializeOwned>(arquivo: &str) -> Result<T, Erro> {
+    let extensao = extensao(arquivo)?;
+    let texto = fs::read_to_string(arquivo).map_err(|e| Erro::leitura(arquivo, e))?;
+    match extensao {
+        Extensao::Json => serde_json::from_str(&texto).map_err(|e| Erro::documento(arquivo, e)),
+        Extensao::Toml => toml::from_str(&texto).map_err(|e| Erro::documento(arquivo, e)),
+    }
+}
+
+// Escreve um documento JSON ou TOML, de acordo com a extensão
+pub fn escreve<T: Serialize>(valor: &T, arquivo: &str) -> Result<(), Erro> {
+    let texto = match extensao(arquivo)? {
+        Extensao::Json => {
+            serde_json::to_string_pretty(valor).map_err(|e| Erro::documento(arquivo, e))?
+        }
+        Extensao::Toml => toml::to_string(valor).map_err(|e| Erro::documento(arquivo, e))?,
+    };
+    fs::write(arquivo, texto).map_err(|e| Erro::escrita(arquivo, e))
+}
+
+pub fn le_instancia(arquivo: &str) -> Result<Instancia, Erro> {
+    le::<InstanciaDoc>(arquivo)?.instancia(arquivo)
+}
+
+pub fn escreve_instancia(inst: &Instancia, arquivo: &str) -> Result<(), Erro> {
+    escreve(&InstanciaDoc::from_instancia(inst), arquivo)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::process;
+    use super::*;
+    use grasp::Grasp;
+    use objetivo::AdiantamentoAtraso;
+    use verificacao;
+
+    // Arquivo temporário exclusivo deste processo
+    fn temporario(nome: &str) -> String {
+        let caminho = env::temp_dir().join(format!("escalonamento-{}-{}", process::id(), nome));
+        caminho.to_str().unwrap().to_string()
+    }
+
+    fn instancia() -> Instancia {
+        let tarefas = vec![Tarefa::completa(3, 9, 2, 1, 0).com_nome("corte".to_string()),
+                           Tarefa::completa(4, 6, 1, 3, 2),
+                           Tarefa::completa(2, 14, 1, 1, 5),
+                           Tarefa::completa(5, 20, 3, 2, 0)];
+        let setup = (0..4).map(|i| (0..4).map(|j| (i + 2 * j) % 3).collect()).collect();
+        Instancia::com_setup(tarefas, setup).unwrap()
+    }
+
+    #[test]
+    fn solucao_ida_e_volta() {
+        let inst = instancia();
+        let av = Avaliador::new(&inst, &AdiantamentoAtraso, Temporizacao::Otima);
+        let mut grasp = Grasp::new(&inst);
+        grasp.temporizacao(Temporizacao::Otima).max_iter_total(3).semente(7);
+        let res = grasp.solve().unwrap();
+        let doc = SolucaoDoc::new(&grasp, &av, &res);
+
+        let fim = av.finalizacao(res.solucao.sequencia());
+        let tempos: Vec<_> = doc.tarefas.iter().map(|t| (t.id, t.inicio, t.fim)).collect();
+        let esperados: Vec<_> = res.solucao
+            .sequencia()
+            .iter()
+            .map(|&id| (id, fim[id] - inst.tarefa(id).duracao(), fim[id]))
+            .collect();
+        assert_eq!(tempos, esperados);
+
+        for extensao in &["json", "toml"] {
+            let arquivo = temporario(&format!("solucao.{}", extensao));
+            escreve(&doc, &arquivo).unwrap();
+            let lido: SolucaoDoc = le(&arquivo).unwrap();
+            let informada = verificacao::le_solucao(&arquivo).unwrap();
+            fs::remove_file(&arquivo).unwrap();
+
+            assert_eq!(lido, doc);
+            assert_eq!(lido.fo, res.solucao.fo());
+            assert_eq!(informada.sequencia(), res.solucao.sequencia());
+            assert_eq!(informada.fo(), res.solucao.fo());
+        }
+    }
+
+    #[test]
+    fn instancia_ida_e_volta() {
+        let inst = instancia();
+        let doc = InstanciaDoc::from_instancia(&inst);
+        for extensao in &["json", "toml"] {
+            let arquivo = temporario(&format!("instancia.{}", extensao));
+            escreve_instancia(&inst, &arquivo).unwrap();
+            let lida = le_instancia(&arquivo).unwrap();
+            fs::remove_file(&arquivo).unwrap();
+
+            assert_eq!(InstanciaDoc::from_instancia(&lida), doc);
+        }
+    }
+
+    #[test]
+    fn documento_malformado() {
+        let casos = [("truncado.json", "{ \"tarefas\": [ { \"id\": 0, \"duracao\": 3"),
+                     ("sem_entrega.toml", "[[tarefas]]\nid = 0\nduracao = 3\n"),
+                     ("repetido.json",
+                      "{ \"tarefas\": [ { \"id\": 0, \"duracao\": 3, \"entrega\": 5 },
+                                        { \"id\": 0, \"duracao\": 2, \"entrega\": 4 } ] }")];
+        for &(nome, texto) in &casos {
+            let arquivo = temporario(nome);
+            fs::write(&arquivo, texto).unwrap();
+            let lida = le_instancia(&arquivo);
+            fs::remove_file(&arquivo).unwrap();
+
+            match lida {
+                Err(Erro::Documento { arquivo: a, mensagem }) => {
+                    assert_eq!(a, arquivo);
+                    if nome == "repetido.json" {
+                        assert_eq!(mensagem, "id 0 repetido");
+                    }
+                }
+                _ => panic!("Documento {} aceito", nome),
+            }
+        }
+
+        match le::<SolucaoDoc>("solucao.txt") {
+            Err(Erro::ExtensaoDesconhecida { .. }) => {}
+            _ => panic!("Extensão .txt aceita"),
+        }
+    }
+
+    #[test]
+    fn erro_de_escrita() {
+        let arquivo = "/inexistente/instancia.json";
+        let erro = escreve_instancia(&instancia(), arquivo).err().unwrap();
+        match erro {
+            Erro::Escrita { .. } => {}
+            _ => panic!("Erro inesperado: {}", erro),
+        }
+        assert!(erro.to_string().starts_with("Erro ao gravar o arquivo /inexistente/instancia.json"));
+
+        match le_instancia(arquivo) {
+            Err(Erro::Leitura { .. }) => {}
+            _ => panic!("Arquivo inexistente lido"),
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use std::error;
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum Erro {
+    // A instância não tem tarefas
+    InstanciaVazia,
+    // A matriz de setup não é n x n
+    SetupInvalido { num_tarefas: usize },
+    ObjetivoInvalido(String),
+    // Parâmetro de um algoritmo fora do intervalo aceito
+    Parametro { nome: &'static str, valor: String },
+    // Falha ao abrir ou ler o arquivo
+    Leitura { arquivo: String, erro: io::Error },
+    // Falha ao criar ou gravar o arquivo
+    Escrita { arquivo: String, erro: io::Error },
+    // Conteúdo inválido na linha `linha` (a partir de 1) do arquivo
+    Formato {
+        arquivo: String,
+        linha: usize,
+        problema: Problema,
+    },
+    // Documento JSON ou TOML malformado ou inconsistente
+    Documento { arquivo: String, mensagem: String },
+    // Extensão de arquivo sem formato associado, com as extensões aceitas
+    ExtensaoDesconhecida { arquivo: String, aceitas: &'static str },
+    // Instância ou configuração fora do que o algoritmo trata
+    NaoSuportado { algoritmo: &'static str, motivo: String },
+}
+
+impl Erro {
+    pub(crate) fn leitura(arquivo: &str, erro: io::Error) -> Erro {
+        Erro::Leitura {
+            arquivo: arquivo.to_string(),
+            erro,
+        }
+    }
+
+    pub(crate) fn escrita(arquivo: &str, erro: io::Error) -> Erro {
+        Erro::Escrita {
+            arquivo: arquivo.to_string(),
+            erro,
+        }
+    }
+
+    pub(crate) fn documento<E: fmt::Display>(arquivo: &str, erro: E) -> Erro {
+        Erro::Documento {
+            arquivo: arquivo.to_string(),
+            mensagem: erro.to_string(),
+        }
+    }
+
+    pub(crate) fn formato(arquivo: &str, linha: usize, problema: Problema) -> Erro {
+        Erro::Formato {
+            arquivo: arquivo.to_string(),
+            linha,
+            problema,
+        }
+    }
+}
+
+// Problemas encontrados ao ler uma instância
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problema {
+    NumeroTarefas(String),
+    Campo(String),
+    Colunas(usize),
+    DuracaoNegativa(i32),
+    TarefasFaltando { declaradas: usize, lidas: usize },
+    TarefasExcedentes { declaradas: usize },
+    SetupColunas { esperadas: usize, encontradas: usize },
+    SetupIncompleto { esperadas: usize, lidas: usize },
+    LinhaExtra,
+    // O arquivo termina no meio de uma instância de um arquivo com várias
+    NumerosFaltando { esperados: usize, lidos: usize },
+}
+
+impl fmt::Display for Problema {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Problema::NumeroTarefas(ref valor) => {
+                write!(f, "número de tarefas inválido: \"{}\"", valor)
+            }
+            Problema::Campo(ref valor) => write!(f, "valor não numérico: \"{}\"", valor),
+            Problema::Colunas(colunas) => {
+                write!(f, "tarefa com {} colunas (esperadas 2, 3, 4 ou 5)", colunas)
+            }
+            Problema::DuracaoNegativa(duracao) => write!(f, "duração negativa: {}", duracao),
+            Problema::TarefasFaltando { declaradas, lidas } => {
+                write!(f, "{} tarefas declaradas, mas {} encontradas", declaradas, lidas)
+            }
+            Problema::TarefasExcedentes { declaradas } => {
+                write!(f, "mais tarefas que as {} declaradas", declaradas)
+            }
+            Problema::SetupColunas { esperadas, encontradas } => {
+                write!(f,
+                       "linha da matriz de setup com {} colunas (esperadas {})",
+                       encontradas,
+                       esperadas)
+            }
+            Problema::SetupIncompleto { esperadas, lidas } => {
+                write!(f, "matriz de setup com {} linhas (esperadas {})", lidas, esperadas)
+            }
+            Problema::LinhaExtra => write!(f, "conteúdo após o fim da instância"),
+            Problema::NumerosFaltando { esperados, lidos } => {
+                write!(f,
+                       "instância incompleta: {} números lidos, {} esperados",
+                       lidos,
+                       esperados)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Erro {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Erro::InstanciaVazia => write!(f, "Instância sem tarefas"),
+            Erro::SetupInvalido { num_tarefas } => {
+                write!(f,
+                       "Matriz de setup inválida: esperada {} x {}",
+                       num_tarefas,
+                       num_tarefas)
+            }
+            Erro::ObjetivoInvalido(ref nome) => write!(f, "Objetivo inválido: {}", nome),
+            Erro::Parametro { nome, ref valor } => {
+                write!(f, "Valor inválido para o parâmetro {}: {}", nome, valor)
+            }
+            Erro::Leitura { ref arquivo, ref erro } => {
+                write!(f, "Erro ao ler o arquivo {}: {}", arquivo, erro)
+            }
+            Erro::Escrita { ref arquivo, ref erro } => {
+                write!(f, "Erro ao gravar o arquivo {}: {}", arquivo, erro)
+            }
+            Erro::Formato { ref arquivo, linha, ref problema } => {
+                write!(f, "{}:{}: {}", arquivo, linha, problema)
+            }
+            Erro::Documento { ref arquivo, ref mensagem } => {
+                write!(f, "{}: {}", arquivo, mensagem)
+            }
+            Erro::ExtensaoDesconhecida { ref arquivo, aceitas } => {
+                write!(f, "Extensão desconhecida (use {}): {}", aceitas, arquivo)
+            }
+            Erro::NaoSuportado { algoritmo, ref motivo } => {
+                write!(f, "{} não suporta {}", algoritmo, motivo)
+            }
+        }
+    }
+}
+
+impl error::Error for Erro {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Erro::Leitura { ref erro, .. } | Erro::Escrita { ref erro, .. } => Some(erro),
+            _ => None,
+        }
+    }
+}
+
+// Erro de parâmetro se `valido` for falso
+pub fn verifica_parametro<T: fmt::Display>(nome: &'static str,
+                                           valor: T,
+                                           valido: bool)
+                                           -> Result<(), Erro> {
+    if valido {
+        Ok(())
+    } else {
+        Err(Erro::Parametro {
+            nome,
+            valor: valor.to_string(),
+        })
+    }
+}
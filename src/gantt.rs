@@ -0,0 +1,156 @@
+use std::fmt::Write;
+use std::fs;
+use erro::Erro;
+use instancia::{Avaliador, Solucao};
+use relatorio::Relatorio;
+
+// Diagrama de Gantt em SVG: uma linha por tarefa, na ordem da sequência, com
+// a entrega marcada e a barra colorida pelo adiantamento ou atraso
+
+const LARGURA: f64 = 960.0;
+const MARGEM_ESQ: f64 = 110.0;
+const MARGEM_DIR: f64 = 30.0;
+const TOPO: f64 = 50.0;
+const ALTURA_LINHA: f64 = 22.0;
+const ALTURA_BARRA: f64 = 14.0;
+const NUM_MARCAS: i32 = 10;
+
+const COR_ADIANTADA: &str = "#4a7fc1";
+const COR_PONTUAL: &str = "#4caf50";
+const COR_ATRASADA: &str = "#d9534f";
+const COR_ENTREGA: &str = "#222222";
+
+fn escapa(texto: &str) -> String {
+    texto.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Intervalo entre as marcas do eixo: 1, 2 ou 5 vezes uma potência de 10
+fn passo_eixo(horizonte: i32) -> i32 {
+    let bruto = (horizonte as f64 / NUM_MARCAS as f64).max(1.0);
+    let base = 10f64.powf(bruto.log10().floor());
+    let passo = [1.0, 2.0, 5.0, 10.0]
+        .iter()
+        .map(|m| m * base)
+        .find(|&p| p >= bruto)
+        .unwrap_or(10.0 * base);
+    passo as i32
+}
+
+pub fn svg(av: &Avaliador, sol: &Solucao) -> String {
+    let rel = Relatorio::new(av, sol.sequencia());
+    let horizonte = rel.tarefas
+        .iter()
+        .map(|t| t.fim.max(t.entrega))
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let escala = (LARGURA - MARGEM_ESQ - MARGEM_DIR) / horizonte as f64;
+    let x = |t: i32| MARGEM_ESQ + t as f64 * escala;
+    let base = TOPO + rel.tarefas.len() as f64 * ALTURA_LINHA;
+    let altura = base + 40.0;
+
+    let mut s = String::new();
+    // Escrever numa String não falha
+    let _ = writeln!(s,
+                     r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" font-family="sans-serif" font-size="12">"#,
+                     LARGURA,
+                     altura);
+    let _ = writeln!(s, r#"<rect width="100%" height="100%" fill="white"/>"#);
+    let _ = writeln!(s,
+                     r#"<text x="{}" y="20" font-size="14">{} - FO {}</text>"#,
+                     MARGEM_ESQ,
+                     escapa(av.objetivo().nome()),
+                     sol.fo());
+
+    // Legenda
+    let legenda = [(COR_ADIANTADA, "adiantada"), (COR_PONTUAL, "no prazo"), (COR_ATRASADA, "atrasada")];
+    for (i, &(cor, nome)) in legenda.iter().enumerate() {
+        let lx = LARGURA - MARGEM_DIR - 400.0 + i as f64 * 100.0;
+        let _ = writeln!(s, r#"<rect x="{}" y="10" width="12" height="12" fill="{}"/>"#, lx, cor);
+        let _ = writeln!(s, r#"<text x="{}" y="21">{}</text>"#, lx + 16.0, nome);
+    }
+    let lx = LARGURA - MARGEM_DIR - 100.0;
+    let _ = writeln!(s,
+                     r#"<line x1="{0}" y1="9" x2="{0}" y2="23" stroke="{1}" stroke-width="2"/>"#,
+                     lx + 6.0,
+                     COR_ENTREGA);
+    let _ = writeln!(s, r#"<text x="{}" y="21">entrega</text>"#, lx + 16.0);
+
+    // Eixo do tempo, com linhas de grade
+    let passo = passo_eixo(horizonte);
+    let mut marca = 0;
+    while marca <= horizonte {
+        let mx = x(marca);
+        let _ = writeln!(s,
+                         r##"<line x1="{0:.1}" y1="{1}" x2="{0:.1}" y2="{2}" stroke="#e0e0e0"/>"##,
+                         mx,
+                         TOPO,
+                         base);
+        let _ = writeln!(s,
+                         r#"<text x="{:.1}" y="{}" text-anchor="middle">{}</text>"#,
+                         mx,
+                         base + 16.0,
+                         marca);
+        marca += passo;
+    }
+    let _ = writeln!(s,
+                     r#"<line x1="{}" y1="{}" x2="{:.1}" y2="{1}" stroke="black"/>"#,
+                     MARGEM_ESQ,
+                     base,
+                     x(horizonte));
+    let _ = writeln!(s,
+                     r#"<text x="{:.1}" y="{}" text-anchor="middle">tempo</text>"#,
+                     (MARGEM_ESQ + LARGURA - MARGEM_DIR) / 2.0,
+                     base + 34.0);
+
+    for (i, t) in rel.tarefas.iter().enumerate() {
+        let y = TOPO + i as f64 * ALTURA_LINHA;
+        let meio = y + ALTURA_LINHA / 2.0;
+        let cor = if t.atraso > 0 {
+            COR_ATRASADA
+        } else if t.adiantamento > 0 {
+            COR_ADIANTADA
+        } else {
+            COR_PONTUAL
+        };
+        let rotulo = match t.nome {
+            Some(ref nome) => format!("{} ({})", escapa(nome), t.id),
+            None => format!("Tarefa {}", t.id),
+        };
+
+        let _ = writeln!(s,
+                         r#"<text x="{}" y="{:.1}" text-anchor="end">{}</text>"#,
+                         MARGEM_ESQ - 8.0,
+                         meio + 4.0,
+                         rotulo);
+        let _ = writeln!(s,
+                         r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{}" fill="{}"><title>{}: {}-{}, entrega {}, adiantamento {}, atraso {}</title></rect>"#,
+                         x(t.inicio),
+                         meio - ALTURA_BARRA / 2.0,
+                         (t.fim - t.inicio) as f64 * escala,
+                         ALTURA_BARRA,
+                         cor,
+                         rotulo,
+                         t.inicio,
+                         t.fim,
+                         t.entrega,
+                         t.adiantamento,
+                         t.atraso);
+        let _ = writeln!(s,
+                         r#"<line x1="{0:.1}" y1="{1:.1}" x2="{0:.1}" y2="{2:.1}" stroke="{3}" stroke-width="2"/>"#,
+                         x(t.entrega),
+                         y + 2.0,
+                         y + ALTURA_LINHA - 2.0,
+                         COR_ENTREGA);
+    }
+
+    let _ = writeln!(s, "</svg>");
+    s
+}
+
+pub fn escreve_svg(av: &Avaliador, sol: &Solucao, arquivo: &str) -> Result<(), Erro> {
+    fs::write(arquivo, svg(av, sol)).map_err(|e| Erro::escrita(arquivo, e))
+}
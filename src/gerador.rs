@@ -0,0 +1,94 @@
+extern crate rand;
+
+use std::cmp::max;
+use self::rand::Rng;
+use erro::{Erro, verifica_parametro};
+use instancia::{Instancia, Tarefa};
+use solver::{gera_rng, semente_aleatoria};
+
+// Gerador de instâncias no esquema de Potts e Van Wassenhove: durações
+// uniformes em [duracao_min, duracao_max] e entregas uniformes em
+// [P(1 - TF - RDD/2), P(1 - TF + RDD/2)], em que P é a soma das durações,
+// TF é o fator de atraso e RDD a amplitude relativa das entregas. Os pesos
+// de adiantamento e atraso são uniformes em [1, peso_max].
+pub struct Gerador {
+    num_tarefas: usize,
+    duracao_min: i32,
+    duracao_max: i32,
+    tf: f64,
+    rdd: f64,
+    peso_max: i32,
+    semente: Option<u64>,
+}
+
+impl Gerador {
+    pub fn new(num_tarefas: usize) -> Gerador {
+        Gerador {
+            num_tarefas,
+            duracao_min: 1,
+            duracao_max: 100,
+            tf: 0.6,
+            rdd: 0.4,
+            peso_max: 1,
+            semente: None,
+        }
+    }
+
+    pub fn duracao(&mut self, duracao_min: i32, duracao_max: i32) -> &mut Gerador {
+        self.duracao_min = duracao_min;
+        self.duracao_max = duracao_max;
+        self
+    }
+
+    pub fn tf(&mut self, tf: f64) -> &mut Gerador {
+        self.tf = tf;
+        self
+    }
+
+    pub fn rdd(&mut self, rdd: f64) -> &mut Gerador {
+        self.rdd = rdd;
+        self
+    }
+
+    pub fn peso_max(&mut self, peso_max: i32) -> &mut Gerador {
+        self.peso_max = peso_max;
+        self
+    }
+
+    pub fn semente(&mut self, semente: u64) -> &mut Gerador {
+        self.semente = Some(semente);
+        self
+    }
+
+    pub fn gera(&self) -> Result<Instancia, Erro> {
+        let fator = |f: f64| (0.0..=1.0).contains(&f);
+        verifica_parametro("num_tarefas", self.num_tarefas, self.num_tarefas > 0)?;
+        verifica_parametro("duracao_min", self.duracao_min, self.duracao_min >= 1)?;
+        verifica_parametro("duracao_max",
+                           self.duracao_max,
+                           self.duracao_max >= self.duracao_min)?;
+        verifica_parametro("tf", self.tf, fator(self.tf))?;
+        verifica_parametro("rdd", self.rdd, fator(self.rdd))?;
+        verifica_parametro("peso_max", self.peso_max, self.peso_max >= 1)?;
+
+        let mut rng = gera_rng(self.semente.unwrap_or_else(semente_aleatoria));
+        let duracoes: Vec<i32> = (0..self.num_tarefas)
+            .map(|_| rng.gen_range(self.duracao_min, self.duracao_max + 1))
+            .collect();
+
+        let total = duracoes.iter().map(|&p| p as f64).sum::<f64>();
+        let entrega_min = max(0, (total * (1.0 - self.tf - self.rdd / 2.0)) as i32);
+        let entrega_max = max(entrega_min, (total * (1.0 - self.tf + self.rdd / 2.0)) as i32);
+
+        let tarefas = duracoes.into_iter()
+            .map(|p| {
+                let entrega = rng.gen_range(entrega_min, entrega_max + 1);
+                let peso_adiantamento = rng.gen_range(1, self.peso_max + 1);
+                let peso_atraso = rng.gen_range(1, self.peso_max + 1);
+                Tarefa::com_pesos(p, entrega, peso_adiantamento, peso_atraso)
+            })
+            .collect();
+
+        Ok(Instancia::new(tarefas))
+    }
+}
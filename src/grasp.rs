@@ -1,96 +1,105 @@
 extern crate rand;
 
-use std::u64;
-use std::time::{Duration, Instant};
+use std::time::Duration;
+use std::cmp::{max, Ordering};
 use self::rand::Rng;
-use instancia::{Instancia, IdTarefa, Solucao, Sequencia};
-
-#[allow(dead_code)]
-pub fn solve(inst: &Instancia,
-             alfa: f64, // 0.3 0.5 0.7
-             timeout: Duration, // 30s
-             num_vizinhos: u32, // 15 30 60
-             max_iter: u64 /* INF */)
-             -> (Solucao, u64, u64) {
-    let mut rng = rand::weak_rng();
-    let t = Instant::now();
-
+use instancia::{Instancia, IdTarefa, Solucao, Avaliador, Temporizacao,
+                verifica_instancia};
+use erro::{Erro, verifica_parametro};
+use solver::{Solver, Resultado, Criterios, Parametros, Incumbente, multi_start, num_threads,
+             semente_aleatoria};
+use objetivo::{Objetivo, AdiantamentoAtraso};
+use vizinhanca::{Delta, Movimento};
+
+pub fn solve<R: Rng + Sized>(rng: &mut R,
+                             av: &Avaliador,
+                             alfa: f64, // 0.3 0.5 0.7
+                             tipo: Construcao,
+                             criterios: &Criterios,
+                             num_vizinhos: u32, // 15 30 60
+                             incumbente: &Incumbente)
+                             -> Resultado {
+    av.limita_avaliacoes(criterios.max_avaliacoes);
     let mut it = 0;
     let mut it_alvo = 0;
     let mut best = Solucao::vazia();
 
-    while it - it_alvo < max_iter && t.elapsed() < timeout {
-        if it % max_iter == 0 {
-            println!("i: {}", it);
+    let parada = loop {
+        if let Some(parada) = criterios.verifica(av, it, it_alvo, incumbente.fo()) {
+            break parada;
         }
 
-        let atual = construcao(&mut rng, inst, alfa);
-        let vizinho = busca_local(&mut rng, inst, &atual, num_vizinhos);
+        let atual = construcao(rng, av, alfa, tipo);
+        let vizinho = vnd(rng, av, &atual, num_vizinhos);
 
         if vizinho.fo() < best.fo() {
             best = vizinho;
             it_alvo = it;
+            av.conta_melhoria();
+            incumbente.atualiza(&best);
         }
 
         it += 1;
+    };
+
+    Resultado {
+        solucao: best,
+        it_alvo,
+        num_iter: it,
+        estatisticas: av.estatisticas(),
+        parada,
+        semente: 0,
     }
-
-    (best, it_alvo, it)
 }
 
-#[allow(dead_code)]
-fn earliest_due_date<R: Rng + Sized>(rng: &mut R,
-                                     inst: &Instancia,
-                                     alfa: f64)
-                                     -> Option<Sequencia> {
-    let num_tarefas = inst.num_tarefas();
-    let mut sequencia = Vec::with_capacity(num_tarefas);
-    let mut marcados = vec![false; num_tarefas];
-    let mut num_marcados = 0;
-
-    let inicial = rng.gen::<IdTarefa>() % num_tarefas;
-    sequencia.push(inicial);
-    marcados[inicial] = true;
-    num_marcados += 1;
-
-    while num_marcados < num_tarefas {
-        let mut abertos: Vec<_> = (0..num_tarefas).filter(|t| !marcados[*t]).collect();
-        abertos.sort_by_key(|t| inst.tarefa(*t).entrega());
-
-        let num_candidatos = (abertos.len() as f64 * alfa).ceil() as usize;
-        if num_candidatos == 0 {
-            return None;
-        }
-
-        let proximo = abertos[rng.gen::<IdTarefa>() % num_candidatos];
-        sequencia.push(proximo);
-
-        marcados[proximo] = true;
-        num_marcados += 1;
-    }
-
-    Some(sequencia)
+// Alfa da construção semigulosa que gera a solução inicial das buscas que
+// partem de uma única solução (busca tabu e recozimento)
+pub const ALFA_INICIAL: f64 = 0.5;
+// Vizinhos sorteados por vizinhança na busca local, por padrão
+pub const NUM_VIZINHOS: u32 = 60;
+
+// Heurística construtiva usada a cada iteração do GRASP
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Construcao {
+    Neh,
+    // Específica para entrega comum
+    FormaV,
 }
 
-#[allow(dead_code)]
-fn construcao<R: Rng + Sized>(mut rng: &mut R, inst: &Instancia, alfa: f64) -> Solucao {
+// Construção semigulosa, repetida até gerar uma sequência completa
+pub fn construcao<R: Rng + Sized>(rng: &mut R,
+                                  av: &Avaliador,
+                                  alfa: f64,
+                                  tipo: Construcao)
+                                  -> Solucao {
     loop {
-        // if let Some(seq) = earliest_due_date(rng, inst, alfa) {
-        //   return Solucao::new(inst, seq);
-        // }
-        if let Some(seq) = neh_semiguloso(rng, inst, alfa) {
-            return seq;
+        let sol = match tipo {
+            Construcao::Neh => neh_semiguloso(rng, av, alfa),
+            Construcao::FormaV => forma_v_semiguloso(rng, av, alfa),
+        };
+        if let Some(sol) = sol {
+            return sol;
         }
     }
 }
 
-fn neh_semiguloso<R: Rng + Sized>(rng: &mut R, inst: &Instancia, alfa: f64) -> Option<Solucao> {
-    let mut sol = Solucao::new(&inst, vec![]);
+// NEH aleatorizado: a próxima tarefa a ser inserida é sorteada entre a fração
+// `alfa` das restantes com maior entrega
+pub fn neh_semiguloso<R: Rng + Sized>(rng: &mut R, av: &Avaliador, alfa: f64) -> Option<Solucao> {
+    let inst = av.inst();
+    let mut sol = Solucao::new(av, vec![]);
     let n = inst.num_tarefas();
     let mut seq: Vec<_> = (0..n).collect();
     seq.sort_by_key(|t| -inst.tarefa(*t).entrega()); // EDD
 
     while !seq.is_empty() {
+        if av.esgotado() {
+            // Sem orçamento, as restantes vão para o início em ordem EDD
+            let mut v: Vec<_> = seq.into_iter().rev().collect();
+            v.extend(sol.sequencia());
+            return Some(Solucao::new(av, v));
+        }
+
         let num_candidatos = (seq.len() as f64 * alfa).ceil() as usize;
         if num_candidatos == 0 {
             return None;
@@ -100,9 +109,12 @@ fn neh_semiguloso<R: Rng + Sized>(rng: &mut R, inst: &Instancia, alfa: f64) -> O
 
         let mut best: Option<Solucao> = None;
         for i in 0..sol.sequencia().len() + 1 {
+            if best.is_some() && av.esgotado() {
+                break;
+            }
             let mut v = sol.sequencia().clone();
             v.insert(i, t);
-            let v = Solucao::new(&inst, v);
+            let v = Solucao::new(av, v);
             if best.is_none() || v.fo() < best.as_ref().unwrap().fo() {
                 best = Some(v);
             }
@@ -113,51 +125,116 @@ fn neh_semiguloso<R: Rng + Sized>(rng: &mut R, inst: &Instancia, alfa: f64) -> O
     Some(sol)
 }
 
-fn insercao<R: Rng + Sized>(rng: &mut R, mut seq: Sequencia) -> Sequencia {
-    let tidx = rng.gen::<usize>() % seq.len();
-    let nidx = rng.gen::<usize>() % seq.len();
-    let t = seq.remove(tidx);
-    seq.insert(nidx, t);
-    seq
+// Compara as razões a.0 / a.1 e b.0 / b.1 sem divisão; denominador nulo
+// equivale a uma razão infinita
+fn compara_razao(a: (i32, i32), b: (i32, i32)) -> Ordering {
+    (a.0 as i64 * b.1 as i64).cmp(&(b.0 as i64 * a.1 as i64))
+}
+
+// Construção para entrega comum d baseada na forma em V das soluções ótimas:
+// as tarefas que terminam até d ficam em ordem não crescente de p/a, e as
+// que terminam depois, em ordem não decrescente de p/b (a e b são os pesos de
+// adiantamento e atraso). As tarefas são consideradas em ordem não crescente
+// de b/a e vão para o conjunto adiantado enquanto couberem antes de d; a
+// próxima é sorteada entre a fração `alfa` das restantes. Se as entregas não
+// forem iguais, d é a média delas.
+pub fn forma_v_semiguloso<R: Rng + Sized>(rng: &mut R,
+                                          av: &Avaliador,
+                                          alfa: f64)
+                                          -> Option<Solucao> {
+    let inst = av.inst();
+    let n = inst.num_tarefas();
+    let entrega = (0..n).map(|t| inst.tarefa(t).entrega() as i64).sum::<i64>() / max(1, n as i64);
+
+    let pesos = |t: IdTarefa| {
+        let t = inst.tarefa(t);
+        (t.peso_adiantamento(), t.peso_atraso())
+    };
+    let mut restantes: Vec<_> = (0..n).collect();
+    restantes.sort_by(|&i, &j| {
+        let (ai, bi) = pesos(i);
+        let (aj, bj) = pesos(j);
+        compara_razao((bj, aj), (bi, ai))
+    });
+
+    let mut adiantadas = vec![];
+    let mut atrasadas = vec![];
+    let mut ocupado = 0;
+    while !restantes.is_empty() {
+        let num_candidatos = (restantes.len() as f64 * alfa).ceil() as usize;
+        if num_candidatos == 0 {
+            return None;
+        }
+        let t = restantes.remove(rng.gen::<usize>() % num_candidatos);
+        let p = inst.tarefa(t).duracao() as i64;
+        if ocupado + p <= entrega {
+            ocupado += p;
+            adiantadas.push(t);
+        } else {
+            atrasadas.push(t);
+        }
+    }
+
+    let duracao = |t: IdTarefa| inst.tarefa(t).duracao();
+    adiantadas.sort_by(|&i, &j| compara_razao((duracao(j), pesos(j).0), (duracao(i), pesos(i).0)));
+    atrasadas.sort_by(|&i, &j| compara_razao((duracao(i), pesos(i).1), (duracao(j), pesos(j).1)));
+
+    adiantadas.extend(atrasadas);
+    Some(Solucao::new(av, adiantadas))
+}
+
+fn insercao<R: Rng + Sized>(rng: &mut R, n: usize) -> Movimento {
+    let tidx = rng.gen::<usize>() % n;
+    let nidx = rng.gen::<usize>() % n;
+    Movimento::Insercao(tidx, nidx)
 }
 
-fn swap<R: Rng + Sized>(rng: &mut R, mut seq: Sequencia) -> Sequencia {
-    let i = rng.gen::<usize>() % seq.len();
-    let j = rng.gen::<usize>() % seq.len();
-    seq.swap(i, j);
-    seq
+fn swap<R: Rng + Sized>(rng: &mut R, n: usize) -> Movimento {
+    let i = rng.gen::<usize>() % n;
+    let j = rng.gen::<usize>() % n;
+    Movimento::Swap(i, j)
 }
 
-fn swap_adj<R: Rng + Sized>(rng: &mut R, mut seq: Sequencia) -> Sequencia {
-    let i = rng.gen::<usize>() % (seq.len() - 1);
-    let j = rng.gen::<usize>() % (seq.len() - 1);
-    seq.swap(i, j);
-    seq.swap(i + 1, j + 1);
-    seq
+fn swap_adj<R: Rng + Sized>(rng: &mut R, n: usize) -> Movimento {
+    let i = rng.gen::<usize>() % (n - 1);
+    let j = rng.gen::<usize>() % (n - 1);
+    Movimento::SwapAdj(i, j)
 }
 
-fn swap_xyz<R: Rng + Sized>(rng: &mut R, mut seq: Sequencia) -> Sequencia {
-    let y = rng.gen::<usize>() % (seq.len() - 2) + 1;
+fn swap_xyz<R: Rng + Sized>(rng: &mut R, n: usize) -> Movimento {
+    let y = rng.gen::<usize>() % (n - 2) + 1;
     let x = rng.gen::<usize>() % y;
-    let z = rng.gen::<usize>() % (seq.len() - y - 1) + y;
-    seq.swap(x, z);
-    seq.swap(y, z);
-    seq
+    let z = rng.gen::<usize>() % (n - y - 1) + y;
+    Movimento::SwapXyz(x, y, z)
 }
 
-fn vnd<R: Rng + Sized>(rng: &mut R,
-                       inst: &Instancia,
-                       solucao: &Solucao,
-                       num_vizinhos: u32)
-                       -> Solucao {
-    let vizinhancas: Vec<fn(&mut R, Sequencia) -> Sequencia> = vec![insercao, swap, swap_adj,
-                                                                    swap_xyz];
+pub(crate) type Operador<R> = fn(&mut R, usize) -> Movimento;
+
+// Movimentos aleatórios válidos para `n` tarefas: o swap de pares adjacentes
+// precisa de duas tarefas e o swap xyz, de três
+pub(crate) fn operadores<R: Rng + Sized>(n: usize) -> Vec<Operador<R>> {
+    let mut operadores: Vec<Operador<R>> = vec![insercao, swap];
+    if n >= 2 {
+        operadores.push(swap_adj);
+    }
+    if n >= 3 {
+        operadores.push(swap_xyz);
+    }
+    operadores
+}
+
+pub(crate) fn vnd<R: Rng + Sized>(rng: &mut R,
+                                  av: &Avaliador,
+                                  solucao: &Solucao,
+                                  num_vizinhos: u32)
+                                  -> Solucao {
+    let vizinhancas = operadores(solucao.sequencia().len());
     let mut k = 0;
     let nv = vizinhancas.len();
     let mut sbest = solucao.clone();
 
-    while k < nv {
-        let sviz = best_improvement(rng, &sbest, inst, vizinhancas[k], num_vizinhos);
+    while k < nv && !av.esgotado() {
+        let sviz = best_improvement(rng, &sbest, av, vizinhancas[k], num_vizinhos);
         if sviz.fo() < sbest.fo() {
             sbest = sviz;
             k = 0;
@@ -171,120 +248,157 @@ fn vnd<R: Rng + Sized>(rng: &mut R,
 
 fn best_improvement<R: Rng + Sized>(rng: &mut R,
                                     solucao: &Solucao,
-                                    inst: &Instancia,
-                                    operador: fn(&mut R, Sequencia) -> Sequencia,
+                                    av: &Avaliador,
+                                    operador: Operador<R>,
                                     num_vizinhos: u32)
                                     -> Solucao {
-    let mut best = solucao.clone();
-    for _ in 0..num_vizinhos {
-        let viz = Solucao::new(inst, operador(rng, solucao.sequencia().clone()));
-        if viz.fo() < best.fo() {
-            best = viz;
+    let delta = Delta::new(av, solucao);
+    let n = solucao.sequencia().len();
+    let mut best: Option<(Movimento, i32)> = None;
+    let mut best_fo = solucao.fo();
+    for _ in (0..num_vizinhos).take_while(|_| !av.esgotado()) {
+        let mov = operador(rng, n);
+        let fo = delta.fo(mov);
+        if fo < best_fo {
+            best = Some((mov, fo));
+            best_fo = fo;
         }
     }
-    best
+
+    match best {
+        Some((mov, fo)) => delta.aplica(mov, fo),
+        None => solucao.clone(),
+    }
 }
 
 #[allow(dead_code)]
-fn busca_local_vizinho(inst: &Instancia, solucao: &Solucao) -> Solucao {
+fn busca_local_vizinho(av: &Avaliador, solucao: &Solucao) -> Solucao {
     let mut atual = solucao.clone();
-    while let Some(nova) = two_opt_loop(inst, &atual) {
+    while let Some(nova) = two_opt_loop(av, &atual) {
         atual = nova;
     }
     atual
 }
 
-fn two_opt_swap(mut sequencia: Sequencia, i: usize, k: usize) -> Sequencia {
-    sequencia[i..k].reverse();
-    sequencia
-}
-
 #[allow(dead_code)]
-fn two_opt_loop(inst: &Instancia, solucao: &Solucao) -> Option<Solucao> {
-    let num_tarefas = inst.num_tarefas();
-    let mut best = solucao.clone();
+fn two_opt_loop(av: &Avaliador, solucao: &Solucao) -> Option<Solucao> {
+    let num_tarefas = av.inst().num_tarefas();
+    let delta = Delta::new(av, solucao);
+    let mut best: Option<(Movimento, i32)> = None;
+    let mut best_fo = solucao.fo();
 
     for i in 0..num_tarefas - 1 {
         for k in i + 1..num_tarefas {
-            let nova = two_opt_swap(solucao.sequencia().clone(), i, k);
-            let nova = Solucao::new(inst, nova);
-            if nova.fo() < best.fo() {
-                best = nova;
+            let mov = Movimento::TwoOpt(i, k);
+            let fo = delta.fo(mov);
+            if fo < best_fo {
+                best = Some((mov, fo));
+                best_fo = fo;
             }
         }
     }
 
-    if best.fo() < solucao.fo() {
-        Some(best)
-    } else {
-        None
-    }
-}
-
-#[allow(dead_code)]
-fn busca_local<R: Rng + Sized>(rng: &mut R,
-                               inst: &Instancia,
-                               s: &Solucao,
-                               num_vizinhos: u32)
-                               -> Solucao {
-    // for _ in 0..num_vizinhos {
-    // s = busca_local_vizinho(inst, &s);
-    // }
-    // s
-
-    vnd(rng, inst, s, num_vizinhos)
+    best.map(|(mov, fo)| delta.aplica(mov, fo))
 }
 
 pub struct Grasp<'a> {
     inst: &'a Instancia,
     alfa: f64,
-    timeout: u64,
+    construcao: Construcao,
     num_vizinhos: u32,
-    max_iter: u64,
+    criterios: Criterios,
+    threads: usize,
+    objetivo: &'a dyn Objetivo,
+    temporizacao: Temporizacao,
+    semente: Option<u64>,
 }
 
 impl<'a> Grasp<'a> {
-    #[allow(dead_code)]
-    pub fn new(inst: &Instancia) -> Grasp {
+    pub fn new(inst: &'a Instancia) -> Grasp<'a> {
         Grasp {
-            inst: inst,
+            inst,
             alfa: 0.5,
-            timeout: 30,
-            num_vizinhos: 60,
-            max_iter: u64::MAX,
+            construcao: Construcao::Neh,
+            num_vizinhos: NUM_VIZINHOS,
+            criterios: Criterios {
+                tempo: Some(Duration::from_secs(30)),
+                ..Criterios::default()
+            },
+            threads: 1,
+            objetivo: &AdiantamentoAtraso,
+            temporizacao: Temporizacao::Semiativa,
+            semente: None,
         }
     }
 
-    #[allow(dead_code)]
     pub fn alfa(&mut self, alfa: f64) -> &mut Grasp<'a> {
         self.alfa = alfa;
         self
     }
 
-    #[allow(dead_code)]
-    pub fn timeout(&mut self, timeout: u64) -> &mut Grasp<'a> {
-        self.timeout = timeout;
+    pub fn construcao(&mut self, construcao: Construcao) -> &mut Grasp<'a> {
+        self.construcao = construcao;
         self
     }
 
-    #[allow(dead_code)]
+    metodos_criterios!();
+
     pub fn num_vizinhos(&mut self, num_vizinhos: u32) -> &mut Grasp<'a> {
         self.num_vizinhos = num_vizinhos;
         self
     }
 
-    #[allow(dead_code)]
-    pub fn max_iter(&mut self, max_iter: u64) -> &mut Grasp<'a> {
-        self.max_iter = max_iter;
+    pub fn objetivo(&mut self, objetivo: &'a dyn Objetivo) -> &mut Grasp<'a> {
+        self.objetivo = objetivo;
         self
     }
 
-    #[allow(dead_code)]
-    pub fn solve(&self) -> (Solucao, u64, u64) {
-        solve(self.inst,
-              self.alfa,
-              Duration::from_secs(self.timeout),
-              self.num_vizinhos,
-              self.max_iter)
+    // Número de threads da execução paralela; 0 usa todos os núcleos
+    pub fn threads(&mut self, threads: usize) -> &mut Grasp<'a> {
+        self.threads = threads;
+        self
+    }
+
+    pub fn semente(&mut self, semente: u64) -> &mut Grasp<'a> {
+        self.semente = Some(semente);
+        self
+    }
+
+    pub fn temporizacao(&mut self, temporizacao: Temporizacao) -> &mut Grasp<'a> {
+        self.temporizacao = temporizacao;
+        self
+    }
+}
+
+impl<'a> Solver for Grasp<'a> {
+    fn nome(&self) -> &'static str {
+        "Grasp"
+    }
+
+    fn parametros(&self) -> Parametros {
+        let mut parametros = vec![("alfa", self.alfa.to_string()),
+                                  ("construcao", format!("{:?}", self.construcao)),
+                                  ("num_vizinhos", self.num_vizinhos.to_string()),
+                                  ("threads", self.threads.to_string())];
+        parametros.extend(self.criterios.parametros());
+        parametros
+    }
+
+    fn solve(&self) -> Result<Resultado, Erro> {
+        verifica_instancia(self.inst)?;
+        verifica_parametro("alfa", self.alfa, self.alfa > 0.0 && self.alfa <= 1.0)?;
+
+        let threads = num_threads(self.threads);
+        let criterios = self.criterios.divide(threads as u64);
+        let semente = self.semente.unwrap_or_else(semente_aleatoria);
+        Ok(multi_start(threads, semente, |rng, incumbente| {
+            solve(rng,
+                  &Avaliador::new(self.inst, self.objetivo, self.temporizacao),
+                  self.alfa,
+                  self.construcao,
+                  &criterios,
+                  self.num_vizinhos,
+                  incumbente)
+        }))
     }
 }
@@ -1,21 +1,28 @@
 extern crate rand;
-use std::io::{BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, Write};
+use std::cell::Cell;
 use std::time::{Duration, Instant};
 use std::path::Path;
 use std::fs::File;
-use std::cmp::max;
+use std::cmp::{min, max};
 use self::rand::Rng;
+use objetivo::{self, Objetivo, AdiantamentoAtraso};
+use erro::{Erro, Problema};
+use solver::{Solver, Resultado, Criterios, Parametros, gera_rng, semente_aleatoria};
+use vizinhanca::{Delta, Movimento};
 
 pub const INF: i32 = 1e9 as i32;
 
-pub static mut CHAMADAS_FO: u64 = 0;
-
 pub type IdTarefa = usize;
 pub type Sequencia = Vec<IdTarefa>;
 
 pub struct Tarefa {
     duracao: i32,
     entrega: i32,
+    peso_adiantamento: i32,
+    peso_atraso: i32,
+    liberacao: i32,
+    nome: Option<String>,
 }
 
 impl Tarefa {
@@ -27,47 +34,244 @@ impl Tarefa {
         self.entrega
     }
 
+    pub fn peso_adiantamento(&self) -> i32 {
+        self.peso_adiantamento
+    }
+
+    pub fn peso_atraso(&self) -> i32 {
+        self.peso_atraso
+    }
+
+    pub fn liberacao(&self) -> i32 {
+        self.liberacao
+    }
+
+    pub fn nome(&self) -> Option<&str> {
+        self.nome.as_deref()
+    }
+
     pub fn new(duracao: i32, entrega: i32) -> Tarefa {
+        Tarefa::completa(duracao, entrega, 1, 1, 0)
+    }
+
+    pub fn com_pesos(duracao: i32,
+                     entrega: i32,
+                     peso_adiantamento: i32,
+                     peso_atraso: i32)
+                     -> Tarefa {
+        Tarefa::completa(duracao, entrega, peso_adiantamento, peso_atraso, 0)
+    }
+
+    pub fn completa(duracao: i32,
+                    entrega: i32,
+                    peso_adiantamento: i32,
+                    peso_atraso: i32,
+                    liberacao: i32)
+                    -> Tarefa {
         Tarefa {
-            duracao: duracao,
-            entrega: entrega,
+            duracao,
+            entrega,
+            peso_adiantamento,
+            peso_atraso,
+            liberacao,
+            nome: None,
         }
     }
+
+    pub fn com_nome(mut self, nome: String) -> Tarefa {
+        self.nome = Some(nome);
+        self
+    }
+
+    pub fn custo(&self, finalizacao: i32) -> i32 {
+        let atraso = max(0, finalizacao - self.entrega);
+        let adiantamento = max(0, self.entrega - finalizacao);
+        self.peso_adiantamento * adiantamento + self.peso_atraso * atraso
+    }
 }
 
-pub struct Instancia(Vec<Tarefa>);
+pub struct Instancia {
+    tarefas: Vec<Tarefa>,
+    // setup[i][j]: tempo de preparação da máquina entre as tarefas i e j
+    setup: Vec<Vec<i32>>,
+}
+
+pub(crate) fn le_numeros(linha: &str) -> Result<Vec<i32>, Problema> {
+    linha.split_whitespace()
+        .map(|campo| campo.parse().map_err(|_| Problema::Campo(campo.to_string())))
+        .collect()
+}
+
+pub(crate) fn abre(arquivo: &str) -> Result<BufReader<File>, Erro> {
+    Ok(BufReader::new(File::open(Path::new(arquivo)).map_err(|e| Erro::leitura(arquivo, e))?))
+}
+
+// Linhas não vazias de `leitor`, com a sua numeração a partir de 1. `arquivo`
+// só identifica a origem nos erros.
+pub(crate) fn le_linhas<R: BufRead>(arquivo: &str, leitor: R) -> Result<Vec<(usize, String)>, Erro> {
+    let mut linhas = vec![];
+    for (i, linha) in leitor.lines().enumerate() {
+        let linha = linha.map_err(|e| Erro::leitura(arquivo, e))?;
+        if !linha.trim().is_empty() {
+            linhas.push((i + 1, linha));
+        }
+    }
+    Ok(linhas)
+}
 
 impl Instancia {
+    pub fn new(tarefas: Vec<Tarefa>) -> Instancia {
+        Instancia {
+            tarefas,
+            setup: vec![],
+        }
+    }
+
+    pub fn com_setup(tarefas: Vec<Tarefa>, setup: Vec<Vec<i32>>) -> Result<Instancia, Erro> {
+        if !setup.is_empty() &&
+           (setup.len() != tarefas.len() || setup.iter().any(|l| l.len() != tarefas.len())) {
+            return Err(Erro::SetupInvalido { num_tarefas: tarefas.len() });
+        }
+        Ok(Instancia { tarefas, setup })
+    }
+
     pub fn num_tarefas(&self) -> usize {
-        self.0.len()
+        self.tarefas.len()
     }
 
     pub fn tarefa(&self, id: IdTarefa) -> &Tarefa {
-        &self.0[id]
+        &self.tarefas[id]
+    }
+
+    pub fn tem_setup(&self) -> bool {
+        !self.setup.is_empty()
+    }
+
+    // Tempo de setup para processar `para` logo após `de`. A primeira
+    // tarefa da sequência não tem setup.
+    pub fn setup(&self, de: Option<IdTarefa>, para: IdTarefa) -> i32 {
+        match de {
+            Some(de) if !self.setup.is_empty() => self.setup[de][para],
+            _ => 0,
+        }
     }
 
     pub fn toy() -> Instancia {
-        Instancia(vec![])
+        Instancia::new(vec![])
     }
 
-    pub fn from_arquivo(file: &str) -> Instancia {
-        let path = Path::new(file);
-        let mut file = BufReader::new(File::open(&path).expect("Erro ao abrir arquivo"));
+    // Lê a instância no formato descrito no README. As linhas em branco são
+    // ignoradas, mas continuam contando na numeração das linhas nos erros.
+    pub fn from_arquivo(arquivo: &str) -> Result<Instancia, Erro> {
+        Instancia::le(arquivo, abre(arquivo)?)
+    }
 
-        let mut num_line = String::new();
-        file.read_line(&mut num_line).unwrap();
-        let n: usize = num_line.trim().parse().expect("Erro ao ler o número de tarefas");
+    // Como `from_arquivo`, mas lendo de `leitor`; `arquivo` só é usado nos
+    // erros
+    pub fn le<R: BufRead>(arquivo: &str, leitor: R) -> Result<Instancia, Erro> {
+        let erro = |linha, problema| Erro::formato(arquivo, linha, problema);
+        let mut linhas = le_linhas(arquivo, leitor)?.into_iter();
+
+        let (linha_n, texto) = match linhas.next() {
+            Some(l) => l,
+            None => return Err(erro(1, Problema::NumeroTarefas(String::new()))),
+        };
+        let n: usize = texto.trim()
+            .parse()
+            .map_err(|_| erro(linha_n, Problema::NumeroTarefas(texto.trim().to_string())))?;
+
+        // n vem do arquivo: a capacidade é limitada pelas linhas existentes
+        let mut tarefas = Vec::with_capacity(min(n, linhas.len()));
+        while tarefas.len() < n {
+            let (linha, texto) = match linhas.next() {
+                Some(l) => l,
+                None => {
+                    return Err(erro(linha_n,
+                                    Problema::TarefasFaltando {
+                                        declaradas: n,
+                                        lidas: tarefas.len(),
+                                    }))
+                }
+            };
+
+            let val = le_numeros(&texto).map_err(|p| erro(linha, p))?;
+            let tarefa = match val.len() {
+                2 => Tarefa::new(val[0], val[1]),
+                3 => Tarefa::completa(val[0], val[1], 1, 1, val[2]),
+                4 => Tarefa::com_pesos(val[0], val[1], val[2], val[3]),
+                5 => Tarefa::completa(val[0], val[1], val[2], val[3], val[4]),
+                colunas => return Err(erro(linha, Problema::Colunas(colunas))),
+            };
+            if tarefa.duracao() < 0 {
+                return Err(erro(linha, Problema::DuracaoNegativa(tarefa.duracao())));
+            }
+            tarefas.push(tarefa);
+        }
 
-        Instancia(file.lines()
-            .take(n)
-            .map(|l| {
-                l.expect("Erro ao ler linha")
-                    .split_whitespace()
-                    .map(|number| number.parse().expect("Erro ao ler dado da tarefa"))
-                    .collect::<Vec<i32>>()
-            })
-            .map(|val| Tarefa::new(val[0], val[1]))
-            .collect())
+        // Matriz de setup opcional, seguida apenas de linhas em branco
+        let mut setup: Vec<Vec<i32>> = vec![];
+        let mut linha_setup = 0;
+        for (linha, texto) in linhas {
+            if setup.len() == n {
+                return Err(erro(linha, Problema::LinhaExtra));
+            }
+
+            let val = le_numeros(&texto).map_err(|p| erro(linha, p))?;
+            if val.len() != n {
+                let problema = if setup.is_empty() && (2..=5).contains(&val.len()) {
+                    Problema::TarefasExcedentes { declaradas: n }
+                } else {
+                    Problema::SetupColunas {
+                        esperadas: n,
+                        encontradas: val.len(),
+                    }
+                };
+                return Err(erro(linha, problema));
+            }
+
+            if setup.is_empty() {
+                linha_setup = linha;
+            }
+            setup.push(val);
+        }
+
+        if !setup.is_empty() && setup.len() < n {
+            return Err(erro(linha_setup,
+                            Problema::SetupIncompleto {
+                                esperadas: n,
+                                lidas: setup.len(),
+                            }));
+        }
+
+        Instancia::com_setup(tarefas, setup)
+    }
+
+    // Escreve a instância no formato lido por `from_arquivo`, usando só as
+    // colunas necessárias
+    pub fn escreve<W: Write>(&self, saida: &mut W) -> io::Result<()> {
+        let com_pesos = self.tarefas.iter().any(|t| t.peso_adiantamento != 1 || t.peso_atraso != 1);
+        let com_liberacao = self.tarefas.iter().any(|t| t.liberacao != 0);
+
+        writeln!(saida, "{}", self.num_tarefas())?;
+        for t in &self.tarefas {
+            write!(saida, "{} {}", t.duracao, t.entrega)?;
+            if com_pesos {
+                write!(saida, " {} {}", t.peso_adiantamento, t.peso_atraso)?;
+            }
+            if com_liberacao {
+                write!(saida, " {}", t.liberacao)?;
+            }
+            writeln!(saida)?;
+        }
+
+        if self.tem_setup() {
+            writeln!(saida)?;
+            for linha in &self.setup {
+                let linha: Vec<_> = linha.iter().map(|s| s.to_string()).collect();
+                writeln!(saida, "{}", linha.join(" "))?;
+            }
+        }
+        Ok(())
     }
 }
 
@@ -78,53 +282,256 @@ pub struct Solucao {
     fo: i32,
 }
 
-fn is_factivel(s: &Sequencia, num_tarefas: usize) -> bool {
-    s.len() == num_tarefas && frequencias(s).into_iter().all(|n| n == 1)
+// Se a sequência é uma permutação das tarefas 0..num_tarefas
+pub fn is_factivel(s: &Sequencia, num_tarefas: usize) -> bool {
+    s.len() == num_tarefas && frequencias(s, num_tarefas).into_iter().all(|n| n == 1)
 }
 
-fn frequencias(sequencia: &Sequencia) -> Vec<u64> {
-    let mut freq = vec![0; sequencia.len()];
-    for &vert in sequencia {
+// Número de ocorrências de cada tarefa 0..num_tarefas na sequência; ids
+// maiores são ignorados
+pub fn frequencias(sequencia: &Sequencia, num_tarefas: usize) -> Vec<u64> {
+    let mut freq = vec![0; num_tarefas];
+    for &vert in sequencia.iter().filter(|&&t| t < num_tarefas) {
         freq[vert] += 1;
     }
     freq
 }
 
-impl Solucao {
-    fn calcula_fo(inst: &Instancia, sequencia: &Sequencia) -> i32 {
-        unsafe {
-            CHAMADAS_FO += 1;
+// Como os tempos de início são definidos para uma sequência fixa.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Temporizacao {
+    // Cada tarefa começa assim que a máquina e a tarefa estão disponíveis
+    Semiativa,
+    // Insere ociosidade quando isso reduz o adiantamento (block-merging).
+    // Só tem efeito em objetivos não regulares.
+    Otima,
+}
+
+// Estatísticas de uma execução
+#[derive(Clone, Copy, Debug)]
+pub struct Estatisticas {
+    pub avaliacoes: u64,
+    pub tempo: Duration,
+    pub melhorias: u64,
+}
+
+// Contexto de uma execução: define como as soluções são avaliadas e
+// acumula as estatísticas da execução.
+pub struct Avaliador<'a> {
+    inst: &'a Instancia,
+    objetivo: &'a dyn Objetivo,
+    temporizacao: Temporizacao,
+    inicio: Instant,
+    avaliacoes: Cell<u64>,
+    melhorias: Cell<u64>,
+    // Orçamento de avaliações consultado dentro das iterações
+    max_avaliacoes: Cell<Option<u64>>,
+}
+
+impl<'a> Avaliador<'a> {
+    pub fn new(inst: &'a Instancia,
+               objetivo: &'a dyn Objetivo,
+               temporizacao: Temporizacao)
+               -> Avaliador<'a> {
+        Avaliador {
+            inst,
+            objetivo,
+            temporizacao,
+            inicio: Instant::now(),
+            avaliacoes: Cell::new(0),
+            melhorias: Cell::new(0),
+            max_avaliacoes: Cell::new(None),
         }
+    }
 
-        let tempo_finalizacao = Solucao::executar_simulacao(inst, sequencia);
-        sequencia.iter()
-            .map(|&id| {
-                let t = inst.tarefa(id);
-                let atraso = max(0, tempo_finalizacao[id] - t.entrega());
-                let adiantamento = max(0, t.entrega() - tempo_finalizacao[id]);
-                adiantamento + atraso
-            })
-            .sum()
+    // Define o orçamento de avaliações que `esgotado` consulta
+    pub fn limita_avaliacoes(&self, max_avaliacoes: Option<u64>) {
+        self.max_avaliacoes.set(max_avaliacoes);
+    }
+
+    // Se o orçamento de avaliações acabou. As buscas consultam o orçamento
+    // para parar no meio de uma iteração, não só entre iterações.
+    pub fn esgotado(&self) -> bool {
+        self.max_avaliacoes.get().is_some_and(|max| self.avaliacoes.get() >= max)
+    }
+
+    pub fn conta_avaliacao(&self) {
+        self.avaliacoes.set(self.avaliacoes.get() + 1);
+    }
+
+    pub fn conta_melhoria(&self) {
+        self.melhorias.set(self.melhorias.get() + 1);
+    }
+
+    pub fn estatisticas(&self) -> Estatisticas {
+        Estatisticas {
+            avaliacoes: self.avaliacoes.get(),
+            tempo: self.inicio.elapsed(),
+            melhorias: self.melhorias.get(),
+        }
+    }
+
+    pub fn inst(&self) -> &'a Instancia {
+        self.inst
+    }
+
+    pub fn objetivo(&self) -> &'a dyn Objetivo {
+        self.objetivo
+    }
+
+    pub fn temporizacao(&self) -> Temporizacao {
+        self.temporizacao
+    }
+
+    // A avaliação incremental de movimentos só vale quando a temporização é
+    // definida pela simulação.
+    pub fn incremental(&self) -> bool {
+        self.temporizacao == Temporizacao::Semiativa || self.objetivo.regular()
     }
 
-    fn executar_simulacao(inst: &Instancia, sequencia: &Sequencia) -> Vec<i32> {
+    // Com objetivos regulares, a temporização ótima é a semiativa.
+    pub fn finalizacao(&self, sequencia: &Sequencia) -> Vec<i32> {
+        match self.temporizacao {
+            Temporizacao::Otima if !self.objetivo.regular() => {
+                Solucao::executar_temporizacao(self.inst, sequencia)
+            }
+            _ => Solucao::executar_simulacao(self.inst, sequencia),
+        }
+    }
+}
+
+impl Solucao {
+    fn calcula_fo(av: &Avaliador, sequencia: &Sequencia) -> i32 {
+        av.conta_avaliacao();
+
+        let inst = av.inst();
+        let tempo_finalizacao = av.finalizacao(sequencia);
+        objetivo::agrega(av.objetivo(),
+                         sequencia.iter().map(|&id| (inst.tarefa(id), tempo_finalizacao[id])))
+    }
+
+    // Tempos de finalização semiativos, indexados pelo id da tarefa
+    pub fn executar_simulacao(inst: &Instancia, sequencia: &Sequencia) -> Vec<i32> {
         let mut finalizacao = vec![0; inst.num_tarefas()];
         let mut t_atual = 0;
+        let mut anterior = None;
         for &id in sequencia {
-            t_atual += inst.tarefa(id).duracao();
+            let t = inst.tarefa(id);
+            t_atual = max(t_atual + inst.setup(anterior, id), t.liberacao()) + t.duracao();
             finalizacao[id] = t_atual;
+            anterior = Some(id);
         }
         finalizacao
     }
 
-    pub fn new(inst: &Instancia, sequencia: Sequencia) -> Solucao {
+    // Tempos de finalização ótimos para a sequência fixa. Cada tarefa é
+    // posicionada na sua data de entrega (ou o mais cedo possível) e, quando
+    // encosta no bloco anterior, o bloco é deslocado para a esquerda enquanto
+    // isso reduzir o custo, unindo-se aos blocos anteriores que alcançar.
+    fn executar_temporizacao(inst: &Instancia, sequencia: &Sequencia) -> Vec<i32> {
+        let n = sequencia.len();
+        let tarefa = |k: usize| inst.tarefa(sequencia[k]);
+        // Setup entre as posições k - 1 e k
+        let setup = |k: usize| if k == 0 {
+            0
+        } else {
+            inst.setup(Some(sequencia[k - 1]), sequencia[k])
+        };
+        let mut fim = vec![0; n];
+        let mut blocos: Vec<usize> = Vec::with_capacity(n);
+
+        for k in 0..n {
+            let t = tarefa(k);
+            let anterior = if k == 0 { 0 } else { fim[k - 1] + setup(k) };
+            let mais_cedo = max(anterior, t.liberacao()) + t.duracao();
+            fim[k] = if t.peso_adiantamento() > 0 {
+                max(mais_cedo, t.entrega())
+            } else {
+                mais_cedo
+            };
+
+            if k == 0 || fim[k] - t.duracao() > anterior {
+                blocos.push(k);
+                continue;
+            }
+
+            loop {
+                let b = *blocos.last().expect("Bloco vazio na temporização");
+                let mut ganho = 0;
+                let mut passo = INF;
+                for (j, f) in fim.iter().enumerate().take(k + 1).skip(b) {
+                    let t = tarefa(j);
+                    if *f > t.entrega() {
+                        ganho += t.peso_atraso();
+                        passo = min(passo, f - t.entrega());
+                    } else {
+                        ganho -= t.peso_adiantamento();
+                    }
+                    passo = min(passo, f - t.duracao() - t.liberacao());
+                }
+                if ganho <= 0 {
+                    break;
+                }
+
+                let inicio = fim[b] - tarefa(b).duracao();
+                let folga = if b == 0 {
+                    inicio
+                } else {
+                    inicio - fim[b - 1] - setup(b)
+                };
+                passo = min(passo, folga);
+                for f in &mut fim[b..k + 1] {
+                    *f -= passo;
+                }
+
+                if passo == folga && b > 0 {
+                    blocos.pop();
+                } else if passo == 0 {
+                    break;
+                }
+            }
+        }
+
+        let mut finalizacao = vec![0; inst.num_tarefas()];
+        for (k, &id) in sequencia.iter().enumerate() {
+            finalizacao[id] = fim[k];
+        }
+        finalizacao
+    }
+
+    // Intervalos [início, fim) em que a máquina fica parada, seja esperando a
+    // liberação da próxima tarefa ou por ociosidade inserida. O tempo de
+    // setup não conta como ociosidade.
+    pub fn ociosidade(&self, av: &Avaliador) -> Vec<(i32, i32)> {
+        let finalizacao = av.finalizacao(&self.sequencia);
+        let inst = av.inst();
+        let mut intervalos = vec![];
+        let mut t_atual = 0;
+        let mut anterior = None;
+        for &id in &self.sequencia {
+            let pronta = t_atual + inst.setup(anterior, id);
+            let inicio = finalizacao[id] - inst.tarefa(id).duracao();
+            if inicio > pronta {
+                intervalos.push((pronta, inicio));
+            }
+            t_atual = finalizacao[id];
+            anterior = Some(id);
+        }
+        intervalos
+    }
+
+    pub fn new(av: &Avaliador, sequencia: Sequencia) -> Solucao {
         Solucao {
-            fo: Solucao::calcula_fo(inst, &sequencia),
-            sequencia: sequencia,
+            fo: Solucao::calcula_fo(av, &sequencia),
+            sequencia,
         }
     }
 
-    #[allow(dead_code)]
+    // Solução cuja FO já foi calculada (ex.: avaliação incremental)
+    pub fn com_fo(sequencia: Sequencia, fo: i32) -> Solucao {
+        Solucao { sequencia, fo }
+    }
+
     pub fn vazia() -> Solucao {
         Solucao {
             fo: INF,
@@ -141,20 +548,28 @@ impl Solucao {
     }
 }
 
-#[allow(dead_code)]
-pub fn neh(inst: &Instancia) -> Solucao {
-    let mut sol = Solucao::new(&inst, vec![]);
+// Erro se a instância não puder ser resolvida pelos algoritmos
+pub fn verifica_instancia(inst: &Instancia) -> Result<(), Erro> {
+    if inst.num_tarefas() == 0 {
+        Err(Erro::InstanciaVazia)
+    } else {
+        Ok(())
+    }
+}
+
+pub fn neh(av: &Avaliador) -> Solucao {
+    let inst = av.inst();
+    let mut sol = Solucao::new(av, vec![]);
     let n = inst.num_tarefas();
     let mut seq: Vec<_> = (0..n).collect();
     seq.sort_by_key(|t| -inst.tarefa(*t).entrega());
 
-    while !seq.is_empty() {
-        let t = seq.pop().unwrap();
+    while let Some(t) = seq.pop() {
         let mut best: Option<Solucao> = None;
-        for i in 0..sol.sequencia().len() {
+        for i in 0..sol.sequencia().len() + 1 {
             let mut v = sol.sequencia().clone();
             v.insert(i, t);
-            let v = Solucao::new(&inst, v);
+            let v = Solucao::new(av, v);
             if best.is_none() || v.fo() < best.as_ref().unwrap().fo() {
                 best = Some(v);
             }
@@ -165,28 +580,280 @@ pub fn neh(inst: &Instancia) -> Solucao {
     sol
 }
 
-pub fn busca(inst: &Instancia) -> Solucao {
+pub fn busca<R: Rng + Sized>(rng: &mut R, av: &Avaliador, criterios: &Criterios) -> Resultado {
+    let inst = av.inst();
     let n = inst.num_tarefas();
     let mut s: Vec<_> = (0..n).collect();
     s.sort_by_key(|t| inst.tarefa(*t).entrega() - inst.tarefa(*t).duracao());
-    let mut s = Solucao::new(&inst, s);
-    let mut rng = rand::weak_rng();
+    av.limita_avaliacoes(criterios.max_avaliacoes);
+    let mut s = Solucao::new(av, s);
+
+    let mut it = 0;
+    let mut it_alvo = 0;
+
+    let parada = loop {
+        if let Some(parada) = criterios.verifica(av, it, it_alvo, s.fo()) {
+            break parada;
+        }
+
+        let i = rng.gen::<usize>() % n;
+        let mut melhor = None;
+        {
+            let delta = Delta::new(av, &s);
+            let mut melhor_fo = s.fo();
+            for j in (0..n).take_while(|_| !av.esgotado()) {
+                let mov = Movimento::Insercao(i, j);
+                let fo = delta.fo(mov);
+                if fo < melhor_fo {
+                    melhor_fo = fo;
+                    melhor = Some(delta.aplica(mov, fo));
+                }
+            }
+        }
+        if let Some(v) = melhor {
+            s = v;
+            it_alvo = it;
+            av.conta_melhoria();
+        }
+        it += 1;
+    };
+
+    Resultado {
+        solucao: s,
+        it_alvo,
+        num_iter: it,
+        estatisticas: av.estatisticas(),
+        parada,
+        semente: 0,
+    }
+}
 
-    let t = Instant::now();
-    let timeout = Duration::from_secs(5);
+// Busca local por inserção a partir da sequência ordenada pelo início mais
+// tardio, até o tempo limite.
+pub struct Busca<'a> {
+    inst: &'a Instancia,
+    criterios: Criterios,
+    objetivo: &'a dyn Objetivo,
+    temporizacao: Temporizacao,
+    semente: Option<u64>,
+}
+
+impl<'a> Busca<'a> {
+    pub fn new(inst: &'a Instancia) -> Busca<'a> {
+        Busca {
+            inst,
+            criterios: Criterios {
+                tempo: Some(Duration::from_secs(5)),
+                ..Criterios::default()
+            },
+            objetivo: &AdiantamentoAtraso,
+            temporizacao: Temporizacao::Semiativa,
+            semente: None,
+        }
+    }
+
+    metodos_criterios!(tempo);
 
-    while t.elapsed() < timeout {
-        let t = rng.gen::<IdTarefa>() % n;
-        for j in 0..n {
-            let mut v = s.sequencia().clone();
-            v.retain(|&x| x != t);
-            v.insert(j, t);
-            let v = Solucao::new(&inst, v);
-            if v.fo() < s.fo() {
-                s = v;
+    pub fn objetivo(&mut self, objetivo: &'a dyn Objetivo) -> &mut Busca<'a> {
+        self.objetivo = objetivo;
+        self
+    }
+
+    pub fn temporizacao(&mut self, temporizacao: Temporizacao) -> &mut Busca<'a> {
+        self.temporizacao = temporizacao;
+        self
+    }
+
+    pub fn semente(&mut self, semente: u64) -> &mut Busca<'a> {
+        self.semente = Some(semente);
+        self
+    }
+}
+
+impl<'a> Solver for Busca<'a> {
+    fn nome(&self) -> &'static str {
+        "Busca"
+    }
+
+    fn parametros(&self) -> Parametros {
+        self.criterios.parametros()
+    }
+
+    fn solve(&self) -> Result<Resultado, Erro> {
+        verifica_instancia(self.inst)?;
+
+        let semente = self.semente.unwrap_or_else(semente_aleatoria);
+        let mut res = busca(&mut gera_rng(semente),
+                            &Avaliador::new(self.inst, self.objetivo, self.temporizacao),
+                            &self.criterios);
+        res.semente = semente;
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rand;
+
+    use self::rand::Rng;
+    use super::*;
+    use solver::gera_rng;
+
+    // Custo ótimo de `sequencia` por programação dinâmica sobre os instantes
+    // de finalização inteiros: menor[c] é o menor custo das tarefas já
+    // sequenciadas com a última terminando até c
+    fn custo_otimo(inst: &Instancia, sequencia: &Sequencia) -> i32 {
+        // Nenhuma tarefa precisa terminar depois deste instante (os setups
+        // dos testes são menores que 10)
+        let horizonte: i32 = sequencia.iter()
+            .map(|&id| inst.tarefa(id))
+            .map(|t| t.entrega() + t.liberacao() + t.duracao() + 10)
+            .sum();
+        let horizonte = horizonte as usize;
+        let mut menor = vec![0; horizonte + 1];
+        let mut anterior = None;
+        for &id in sequencia {
+            let t = inst.tarefa(id);
+            let setup = inst.setup(anterior, id) as usize;
+            let mut custo = vec![INF; horizonte + 1];
+            for (c, custo) in custo.iter_mut().enumerate() {
+                let inicio = c as i32 - t.duracao();
+                if inicio < t.liberacao() {
+                    continue;
+                }
+                // Sem tarefa anterior, não há restrição além da liberação
+                let antes = match anterior {
+                    None => 0,
+                    Some(_) if inicio as usize >= setup => menor[inicio as usize - setup],
+                    Some(_) => INF,
+                };
+                if antes < INF {
+                    *custo = antes + t.custo(c as i32);
+                }
             }
+            for c in 1..custo.len() {
+                custo[c] = min(custo[c], custo[c - 1]);
+            }
+            menor = custo;
+            anterior = Some(id);
+        }
+        menor[horizonte]
+    }
+
+    #[test]
+    fn temporizacao_igual_programacao_dinamica() {
+        for semente in 0..500 {
+            let mut rng = gera_rng(semente);
+            let n = rng.gen_range(1, 7);
+            let tarefas = (0..n)
+                .map(|_| {
+                    Tarefa::completa(rng.gen_range(1, 6),
+                                     rng.gen_range(0, 30),
+                                     rng.gen_range(0, 4),
+                                     rng.gen_range(0, 4),
+                                     rng.gen_range(0, 20))
+                })
+                .collect();
+            let setup = (0..n)
+                .map(|i| (0..n).map(|j| if i == j { 0 } else { rng.gen_range(0, 4) }).collect())
+                .collect();
+            let inst = Instancia::com_setup(tarefas, setup).unwrap();
+            let mut seq: Sequencia = (0..n).collect();
+            rng.shuffle(&mut seq);
+
+            let fim = Solucao::executar_temporizacao(&inst, &seq);
+            let mut pronta = 0;
+            let mut anterior = None;
+            for &id in &seq {
+                let t = inst.tarefa(id);
+                let inicio = fim[id] - t.duracao();
+                assert!(inicio >= t.liberacao() && inicio >= pronta + inst.setup(anterior, id),
+                        "Tarefa {} começa cedo demais, semente {}",
+                        id,
+                        semente);
+                pronta = fim[id];
+                anterior = Some(id);
+            }
+
+            let custo: i32 = seq.iter().map(|&id| inst.tarefa(id).custo(fim[id])).sum();
+            assert_eq!(custo, custo_otimo(&inst, &seq), "semente {}", semente);
+        }
+    }
+
+    fn le(texto: &str) -> Result<Instancia, Erro> {
+        Instancia::le("inst.txt", texto.as_bytes())
+    }
+
+    // Linha e problema do erro de formato ao ler `texto`
+    fn erro(texto: &str) -> (usize, Problema) {
+        match le(texto) {
+            Err(Erro::Formato { linha, problema, .. }) => (linha, problema),
+            Err(e) => panic!("Erro inesperado: {}", e),
+            Ok(_) => panic!("Instância aceita: {:?}", texto),
         }
     }
 
-    s
+    #[test]
+    fn le_formatos_de_colunas() {
+        let inst = le("4\n3 5\n\n2 7 4\n1 6 2 3\n4 9 0 2 1\n").unwrap();
+        let tarefas: Vec<_> = (0..inst.num_tarefas())
+            .map(|id| {
+                let t = inst.tarefa(id);
+                (t.duracao(), t.entrega(), t.peso_adiantamento(), t.peso_atraso(), t.liberacao())
+            })
+            .collect();
+        assert_eq!(tarefas, [(3, 5, 1, 1, 0), (2, 7, 1, 1, 4), (1, 6, 2, 3, 0), (4, 9, 0, 2, 1)]);
+        assert!(!inst.tem_setup());
+
+        let inst = le("2\n3 5\n2 7\n0 4\n1 0\n").unwrap();
+        assert_eq!(inst.setup(Some(0), 1), 4);
+        assert_eq!(inst.setup(Some(1), 0), 1);
+        assert_eq!(inst.setup(None, 1), 0);
+    }
+
+    #[test]
+    fn erros_de_formato() {
+        assert_eq!(erro(""), (1, Problema::NumeroTarefas(String::new())));
+        assert_eq!(erro("dois\n1 2\n"), (1, Problema::NumeroTarefas("dois".to_string())));
+        assert_eq!(erro("-1\n"), (1, Problema::NumeroTarefas("-1".to_string())));
+        assert_eq!(erro("3\n1 2\n3 4\n"),
+                   (1,
+                    Problema::TarefasFaltando {
+                        declaradas: 3,
+                        lidas: 2,
+                    }));
+        assert_eq!(erro("2\n1 2\n\n3 x\n"), (4, Problema::Campo("x".to_string())));
+        assert_eq!(erro("1\n-3 2\n"), (2, Problema::DuracaoNegativa(-3)));
+        assert_eq!(erro("1\n1\n"), (2, Problema::Colunas(1)));
+        assert_eq!(erro("1\n1 2 3 4 5 6\n"), (2, Problema::Colunas(6)));
+        assert_eq!(erro("1\n1 2\n3 4\n"), (3, Problema::TarefasExcedentes { declaradas: 1 }));
+        assert_eq!(erro("2\n1 2\n3 4\n0 1\n1 0 2\n"),
+                   (5,
+                    Problema::SetupColunas {
+                        esperadas: 2,
+                        encontradas: 3,
+                    }));
+        assert_eq!(erro("3\n1 2\n3 4\n5 6\n0 1 2\n1 0 2\n"),
+                   (5,
+                    Problema::SetupIncompleto {
+                        esperadas: 3,
+                        lidas: 2,
+                    }));
+        assert_eq!(erro("1\n1 2\n0\n7\n"), (4, Problema::LinhaExtra));
+    }
+
+    #[test]
+    fn mensagens_de_erro() {
+        let mensagem = |texto| le(texto).err().unwrap().to_string();
+        assert_eq!(mensagem("2\n1 2\n3 x\n"), "inst.txt:3: valor não numérico: \"x\"");
+        assert_eq!(mensagem("1\n1 2 3 4 5 6\n"),
+                   "inst.txt:2: tarefa com 6 colunas (esperadas 2, 3, 4 ou 5)");
+        assert_eq!(mensagem("3\n1 2\n"), "inst.txt:1: 3 tarefas declaradas, mas 1 encontradas");
+        assert_eq!(mensagem("1\n1 2\n0\n7\n"), "inst.txt:4: conteúdo após o fim da instância");
+
+        match Instancia::from_arquivo("/inexistente/inst.txt") {
+            Err(Erro::Leitura { arquivo, .. }) => assert_eq!(arquivo, "/inexistente/inst.txt"),
+            _ => panic!("Arquivo inexistente aceito"),
+        }
+    }
 }
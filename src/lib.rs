@@ -0,0 +1,36 @@
+extern crate serde;
+extern crate serde_json;
+extern crate toml;
+
+#[macro_use]
+pub mod solver;
+pub mod instancia;
+pub mod grasp;
+pub mod ag;
+pub mod objetivo;
+pub mod vizinhanca;
+pub mod erro;
+pub mod gerador;
+pub mod orlib;
+pub mod documento;
+pub mod verificacao;
+pub mod relatorio;
+pub mod gantt;
+pub mod bb;
+pub mod pd;
+pub mod mip;
+pub mod tabu;
+pub mod recozimento;
+
+pub use instancia::{Instancia, Tarefa, Solucao, Sequencia, IdTarefa, Avaliador, Temporizacao,
+                    Estatisticas, Busca, neh, busca};
+pub use grasp::Grasp;
+pub use ag::{Ag, Cruzamento, Mutacao};
+pub use tabu::Tabu;
+pub use recozimento::{Recozimento, Resfriamento};
+pub use objetivo::Objetivo;
+pub use solver::{Solver, Resultado, Parada, Criterios};
+pub use erro::Erro;
+pub use gerador::Gerador;
+pub use bb::BranchBound;
+pub use pd::ProgramacaoDinamica;
@@ -1,104 +1,436 @@
-mod grasp;
-mod instancia;
-mod ag;
+extern crate escalonamento;
 
 use std::env;
-use std::io;
+use std::io::{self, Write};
 use std::process;
-use std::time::Instant;
-use instancia::{Instancia, INF, busca};
-use grasp::Grasp;
-use ag::{Ag, Cruzamento, Mutacao};
+use std::str::FromStr;
+use std::time::Duration;
+use escalonamento::{Instancia, Busca, Avaliador, Temporizacao, Grasp, Ag, Cruzamento, Mutacao,
+                    Objetivo, Solver, Resultado, Criterios, Erro, Gerador, BranchBound,
+                    ProgramacaoDinamica, Tabu, Recozimento, Resfriamento};
+use escalonamento::instancia::INF;
+use escalonamento::objetivo::{self, AdiantamentoAtraso};
+use escalonamento::documento::{self, SolucaoDoc};
+use escalonamento::grasp::Construcao;
+use escalonamento::orlib;
+use escalonamento::verificacao;
+use escalonamento::relatorio::{Relatorio, FormatoRelatorio};
+use escalonamento::gantt;
+use escalonamento::mip::{self, Modelo};
+use escalonamento::solver::semente_aleatoria;
 
 const NUM_EXEC: u32 = 10;
 
-#[allow(dead_code)]
-fn teste_grasp(inst: &Instancia) {
-    unsafe {
-        instancia::CHAMADAS_FO = 0;
+// Formato do arquivo de entrada
+enum Formato {
+    Padrao,
+    // OR-Library wt40/wt50/wt100, com o número de tarefas de cada instância
+    Wt(usize),
+    // OR-Library sch (Biskup e Feldmann), com o fator de restrição h
+    Sch(f64),
+}
+
+// Opções passadas na linha de comando com `--`
+struct Opcoes {
+    temporizacao: Temporizacao,
+    objetivo: &'static dyn Objetivo,
+    semente: Option<u64>,
+    // Critérios de parada que substituem os padrões de cada algoritmo
+    criterios: Criterios,
+    // Threads de GRASP e AG; 0 usa todos os núcleos
+    threads: usize,
+    formato: Formato,
+    // Instância escolhida em arquivos com várias, a partir de 1
+    indice: usize,
+    construcao: Construcao,
+    // Arquivo JSON ou TOML em que a solução é gravada
+    solucao: Option<String>,
+    // Arquivo JSON ou TOML em que a instância lida ou gerada é gravada
+    instancia: Option<String>,
+    // Formato do relatório dos tempos de cada tarefa da solução
+    relatorio: Option<FormatoRelatorio>,
+    // Arquivo SVG em que o diagrama de Gantt da solução é gravado
+    gantt: Option<String>,
+    // Arquivo MST ou SOL em que a solução do GRASP é gravada como início do MIP
+    inicio: Option<String>,
+}
+
+impl Opcoes {
+    fn avaliador<'a>(&self, inst: &'a Instancia) -> Avaliador<'a> {
+        Avaliador::new(inst, self.objetivo, self.temporizacao)
     }
 
-    println!("Grasp");
-    let t = Instant::now();
-    let (solucao, it_alvo, num_iter) = Grasp::new(inst).solve();
-    let tempo = t.elapsed();
+    // Semente da i-ésima execução: consecutivas a partir de --seed, ou
+    // aleatórias se a opção não foi passada
+    fn semente(&self, i: u32) -> u64 {
+        match self.semente {
+            Some(semente) => semente.wrapping_add(i as u64),
+            None => semente_aleatoria(),
+        }
+    }
+}
+
+// Valor numérico da opção `opcao`, lido do próximo argumento
+fn le_valor<T: FromStr, I: Iterator<Item = String>>(args: &mut I, opcao: &str) -> T {
+    let valor = args.next().unwrap_or_default();
+    valor.parse().unwrap_or_else(|_| {
+        println!("Valor inválido para {}: {}", opcao, valor);
+        process::exit(1);
+    })
+}
 
-    println!("Sequencia: {:?}", solucao.sequencia());
-    println!("Iteração alvo: {}", it_alvo);
-    println!("Total iteraçõees: {}", num_iter);
-    println!("Fo: {}", solucao.fo());
-    println!("Tempo: {}.{}", tempo.as_secs(), tempo.subsec_nanos());
-    unsafe {
-        println!("Chamadas FO: {}", instancia::CHAMADAS_FO);
+// Separa as opções dos argumentos posicionais
+fn le_opcoes<I: Iterator<Item = String>>(mut args: I) -> (Vec<String>, Opcoes) {
+    let mut posicionais = vec![];
+    let mut opcoes = Opcoes {
+        temporizacao: Temporizacao::Semiativa,
+        objetivo: &AdiantamentoAtraso,
+        semente: None,
+        criterios: Criterios::default(),
+        threads: 1,
+        formato: Formato::Padrao,
+        indice: 1,
+        construcao: Construcao::Neh,
+        solucao: None,
+        instancia: None,
+        relatorio: None,
+        gantt: None,
+        inicio: None,
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--otima" => opcoes.temporizacao = Temporizacao::Otima,
+            "--objetivo" => {
+                let nome = args.next().unwrap_or_default();
+                opcoes.objetivo = objetivo::from_nome(&nome).unwrap_or_else(|e| falha(e));
+            }
+            "--seed" => opcoes.semente = Some(le_valor(&mut args, &arg)),
+            "--timeout-ms" => {
+                let ms = le_valor(&mut args, &arg);
+                opcoes.criterios.tempo = Some(Duration::from_millis(ms));
+            }
+            "--max-iter" => opcoes.criterios.max_iter = Some(le_valor(&mut args, &arg)),
+            "--max-iter-total" => {
+                opcoes.criterios.max_iter_total = Some(le_valor(&mut args, &arg))
+            }
+            "--max-avaliacoes" => {
+                opcoes.criterios.max_avaliacoes = Some(le_valor(&mut args, &arg))
+            }
+            "--alvo" => opcoes.criterios.alvo = Some(le_valor(&mut args, &arg)),
+            "--threads" => opcoes.threads = le_valor(&mut args, &arg),
+            "--wt" => opcoes.formato = Formato::Wt(le_valor(&mut args, &arg)),
+            "--sch" => opcoes.formato = Formato::Sch(le_valor(&mut args, &arg)),
+            "--indice" => opcoes.indice = le_valor(&mut args, &arg),
+            "--solucao" => opcoes.solucao = args.next(),
+            "--instancia" => opcoes.instancia = args.next(),
+            "--gantt" => opcoes.gantt = args.next(),
+            "--inicio" => opcoes.inicio = args.next(),
+            "--construcao" => {
+                opcoes.construcao = match args.next().unwrap_or_default().as_str() {
+                    "neh" => Construcao::Neh,
+                    "v" => Construcao::FormaV,
+                    nome => {
+                        println!("Construção inválida: {}", nome);
+                        process::exit(1);
+                    }
+                }
+            }
+            "--relatorio" => {
+                opcoes.relatorio = match args.next().unwrap_or_default().as_str() {
+                    "tabela" => Some(FormatoRelatorio::Tabela),
+                    "csv" => Some(FormatoRelatorio::Csv),
+                    "json" => Some(FormatoRelatorio::Json),
+                    nome => {
+                        println!("Formato de relatório inválido: {}", nome);
+                        process::exit(1);
+                    }
+                }
+            }
+            _ if arg.starts_with("--") => {
+                println!("Opção inválida: {}", arg);
+                process::exit(1);
+            }
+            _ => posicionais.push(arg),
+        }
     }
+
+    (posicionais, opcoes)
+}
+
+fn falha(erro: Erro) -> ! {
+    println!("Erro: {}", erro);
+    process::exit(1);
+}
+
+fn resolve(solver: &dyn Solver) -> Resultado {
+    solver.solve().unwrap_or_else(|e| falha(e))
+}
+
+fn executa(solver: &dyn Solver, av: &Avaliador, opcoes: &Opcoes) {
+    println!("{}", solver.nome());
+    mostra(solver, av, opcoes, resolve(solver));
+}
+
+// Mostra o resultado de `solver` e grava o relatório e os arquivos pedidos
+fn mostra(solver: &dyn Solver, av: &Avaliador, opcoes: &Opcoes, res: Resultado) {
+    let tempo = res.estatisticas.tempo;
+
+    println!("Sequencia: {:?}", res.solucao.sequencia());
+    println!("Iteração alvo: {}", res.it_alvo);
+    println!("Total iteraçõees: {}", res.num_iter);
+    println!("Fo: {}", res.solucao.fo());
+    println!("Ociosidade: {:?}", res.solucao.ociosidade(av));
+    println!("Tempo: {}.{:09}", tempo.as_secs(), tempo.subsec_nanos());
+    println!("Chamadas FO: {}", res.estatisticas.avaliacoes);
+    println!("Melhorias: {}", res.estatisticas.melhorias);
+    println!("Parada: {:?}", res.parada);
+    println!("Semente: {}", res.semente);
     println!("-------------------\n");
+
+    if let Some(formato) = opcoes.relatorio {
+        let stdout = io::stdout();
+        Relatorio::new(av, res.solucao.sequencia())
+            .escreve(&mut stdout.lock(), formato)
+            .expect("Erro ao escrever o relatório");
+        println!();
+    }
+
+    if let Some(ref arquivo) = opcoes.solucao {
+        documento::escreve(&SolucaoDoc::new(solver, av, &res), arquivo).unwrap_or_else(|e| falha(e));
+    }
+
+    if let Some(ref arquivo) = opcoes.gantt {
+        gantt::escreve_svg(av, &res.solucao, arquivo).unwrap_or_else(|e| falha(e));
+    }
+}
+
+fn teste_grasp(av: &Avaliador, opcoes: &Opcoes) {
+    executa(Grasp::new(av.inst())
+                .construcao(opcoes.construcao)
+                .objetivo(av.objetivo())
+                .temporizacao(av.temporizacao())
+                .semente(opcoes.semente(0))
+                .criterios(&opcoes.criterios)
+                .threads(opcoes.threads),
+            av,
+            opcoes);
+}
+
+fn teste_ag(av: &Avaliador, opcoes: &Opcoes) {
+    executa(Ag::new(av.inst())
+                .objetivo(av.objetivo())
+                .temporizacao(av.temporizacao())
+                .semente(opcoes.semente(0))
+                .max_iter(INF as u64)
+                .timeout(15)
+                .mut_chance(0.3)
+                .pop_tam(1000)
+                .xo_chance(1.0)
+                .criterios(&opcoes.criterios)
+                .threads(opcoes.threads),
+            av,
+            opcoes);
+}
+
+fn teste_tabu(av: &Avaliador, opcoes: &Opcoes) {
+    executa(Tabu::new(av.inst())
+                .construcao(opcoes.construcao)
+                .objetivo(av.objetivo())
+                .temporizacao(av.temporizacao())
+                .semente(opcoes.semente(0))
+                .criterios(&opcoes.criterios)
+                .threads(opcoes.threads),
+            av,
+            opcoes);
 }
 
-#[allow(dead_code)]
-fn teste_ag(inst: &Instancia) {
-    unsafe {
-        instancia::CHAMADAS_FO = 0;
+fn teste_sa(av: &Avaliador, opcoes: &Opcoes) {
+    executa(Recozimento::new(av.inst())
+                .construcao(opcoes.construcao)
+                .objetivo(av.objetivo())
+                .temporizacao(av.temporizacao())
+                .semente(opcoes.semente(0))
+                .criterios(&opcoes.criterios)
+                .threads(opcoes.threads),
+            av,
+            opcoes);
+}
+
+fn teste_neh(av: &Avaliador, opcoes: &Opcoes) {
+    executa(Busca::new(av.inst())
+                .objetivo(av.objetivo())
+                .temporizacao(av.temporizacao())
+                .semente(opcoes.semente(0))
+                .criterios(&opcoes.criterios),
+            av,
+            opcoes);
+}
+
+fn verificar(av: &Avaliador, arquivo: &str) {
+    let informada = verificacao::le_solucao(arquivo).unwrap_or_else(|e| falha(e));
+    let v = verificacao::verifica(av, informada.sequencia());
+
+    if !v.factivel() {
+        println!("A sequência não é uma permutação das {} tarefas", av.inst().num_tarefas());
+        if !v.repetidas.is_empty() {
+            println!("Repetidas: {:?}", v.repetidas);
+        }
+        if !v.faltando.is_empty() {
+            println!("Faltando: {:?}", v.faltando);
+        }
+        if !v.invalidas.is_empty() {
+            println!("Inexistentes: {:?}", v.invalidas);
+        }
+        process::exit(1);
     }
-    println!("AG");
-    let t = Instant::now();
-    let (solucao, it_alvo, num_iter) = Ag::new(inst)
-        .max_iter(INF as u64)
-        .timeout(15)
-        .mut_chance(0.3)
-        .pop_tam(1000)
-        .xo_chance(1.0)
-        .solve();
-    let tempo = t.elapsed();
-
-    println!("Sequencia: {:?}", solucao.sequencia());
-    println!("Iteração alvo: {}", it_alvo);
-    println!("Total iteraçõees: {}", num_iter);
-    println!("Fo: {}", solucao.fo());
-    println!("Tempo: {}.{}", tempo.as_secs(), tempo.subsec_nanos());
-    unsafe {
-        println!("Chamadas FO: {}", instancia::CHAMADAS_FO);
+
+    println!("{:>5} {:>6} {:>8} {:>8} {:>8} {:>8}",
+             "Pos",
+             "Tarefa",
+             "Inicio",
+             "Fim",
+             "Entrega",
+             "Custo");
+    for (pos, t) in v.tarefas.iter().enumerate() {
+        println!("{:>5} {:>6} {:>8} {:>8} {:>8} {:>8}",
+                 pos,
+                 t.id,
+                 t.inicio,
+                 t.fim,
+                 t.entrega,
+                 t.custo);
     }
-    println!("-------------------\n");
+
+    let fo = v.fo.unwrap();
+    println!("FO informada: {}", informada.fo());
+    println!("FO recalculada: {}", fo);
+    if !v.confere(informada.fo()) {
+        println!("A FO informada não confere");
+        process::exit(1);
+    }
+    println!("OK");
 }
 
-fn teste(inst: Instancia) {
-    println!("Executando testes...");
-    teste_ag(&inst);
-    teste_grasp(&inst);
+fn teste_bb(av: &Avaliador, opcoes: &Opcoes) {
+    let mut bb = BranchBound::new(av.inst());
+    bb.objetivo(av.objetivo())
+        .temporizacao(av.temporizacao())
+        .semente(opcoes.semente(0))
+        .criterios(&opcoes.criterios);
+
+    println!("{}", bb.nome());
+    let res = bb.resolve().unwrap_or_else(|e| falha(e));
+    println!("Limite inferior: {}", res.limite_inferior);
+    println!("Gap: {:.2}%", 100.0 * res.gap());
+    println!("Ótimo: {}", if res.otimo() { "sim" } else { "não" });
+    mostra(&bb, av, opcoes, res.resultado);
+}
+
+fn teste_pd(av: &Avaliador, opcoes: &Opcoes) {
+    executa(ProgramacaoDinamica::new(av.inst())
+                .objetivo(av.objetivo())
+                .temporizacao(av.temporizacao()),
+            av,
+            opcoes);
 }
 
-fn experimento_grasp(inst: Instancia, config: &[&str]) {
-    let id = config[0];
-    let alfa: f64 = config[1].parse().expect("Erro ao ler o Alfa do GRASP");
-    let num_vizinhos: u32 = config[2].parse().expect("Erro ao ler o número de vizinhos do GRASP");
+// Grava o modelo MIP da instância e, com --inicio, a solução do GRASP como
+// solução inicial
+fn exporta_mip(av: &Avaliador, opcoes: &Opcoes, arquivo: &str) {
+    let inst = av.inst();
+    let modelo = Modelo::new(inst, av.objetivo(), av.temporizacao()).unwrap_or_else(|e| falha(e));
+    mip::escreve_modelo(&modelo, arquivo).unwrap_or_else(|e| falha(e));
+    println!("Modelo: {} variáveis, {} restrições",
+             modelo.num_variaveis(),
+             modelo.num_restricoes());
+
+    if let Some(ref inicio) = opcoes.inicio {
+        let res = resolve(Grasp::new(inst)
+            .construcao(opcoes.construcao)
+            .objetivo(av.objetivo())
+            .temporizacao(av.temporizacao())
+            .semente(opcoes.semente(0))
+            .criterios(&opcoes.criterios)
+            .threads(opcoes.threads));
+        let sequencia = res.solucao.sequencia();
+        let valores = modelo.valores(inst, sequencia, &av.finalizacao(sequencia));
+        mip::escreve_inicio(&modelo, &valores, res.solucao.fo(), inicio)
+            .unwrap_or_else(|e| falha(e));
+        println!("Solução inicial: {}", res.solucao.fo());
+        println!("Semente: {}", res.semente);
+    }
+}
 
-    let mut grasp = Grasp::new(&inst);
-    grasp.alfa(alfa).num_vizinhos(num_vizinhos).max_iter(INF as u64).timeout(30);
+fn teste(av: &Avaliador, opcoes: &Opcoes) {
+    println!("Executando testes...");
+    teste_ag(av, opcoes);
+    teste_grasp(av, opcoes);
+}
 
-    println!("ID,iExec,FO,IterAlvo,TotalIter,Tempo");
+// Executa o experimento NUM_EXEC vezes, cada uma com a sua semente, e mostra
+// uma linha de CSV por execução
+fn executa_experimento<S: Solver>(id: &str,
+                                  opcoes: &Opcoes,
+                                  solver: &mut S,
+                                  semente: fn(&mut S, u64) -> &mut S) {
+    println!("ID,iExec,FO,IterAlvo,TotalIter,Tempo,Avaliacoes,Semente,Parada");
     for i in 0..NUM_EXEC {
-        let t = Instant::now();
-        let (solucao, iter_alvo, total_iter) = grasp.solve();
-        let tempo = t.elapsed();
+        let res = resolve(semente(solver, opcoes.semente(i)));
+        let tempo = res.estatisticas.tempo;
 
-        println!("{},{},{},{},{},{}.{}",
+        println!("{},{},{},{},{},{}.{:09},{},{},{:?}",
                  id,
                  i,
-                 solucao.fo(),
-                 iter_alvo,
-                 total_iter,
+                 res.solucao.fo(),
+                 res.it_alvo,
+                 res.num_iter,
                  tempo.as_secs(),
-                 tempo.subsec_nanos());
+                 tempo.subsec_nanos(),
+                 res.estatisticas.avaliacoes,
+                 res.semente,
+                 res.parada);
+    }
+}
+
+// Valores da configuração do experimento, após o nome do algoritmo. Sai com o
+// formato `uso` se o número de valores não for o esperado.
+fn le_config(config: &[&str], uso: &str) -> Vec<String> {
+    if config.len() + 1 != uso.split_whitespace().count() {
+        println!("Uso: {}", uso);
+        process::exit(1);
     }
+    config.iter().map(|v| v.to_string()).collect()
 }
 
-fn experimento_ag(inst: Instancia, config: &[&str]) {
-    let id = config[0];
-    let pop_tam: usize = config[1].parse().expect("Erro ao ler tamanho da população do AG");
-    let xo_chance: f64 = config[2].parse().expect("Erro ao ler chance de cruzamento do AG");
-    let cruz: u32 = config[3].parse().expect("Erro ao ler operador de cruzamento do AG");
-    let mutacao: u32 = config[4].parse().expect("Erro ao ler operador de mutação do AG");
-    let mut_chance: f64 = config[5].parse().expect("Erro ao ler chance de mutação do AG");
+fn experimento_grasp(inst: &Instancia, opcoes: &Opcoes, config: &[&str]) {
+    let mut valores = le_config(config, "grasp ID Alfa NumVizinhos").into_iter();
+    let id: String = le_valor(&mut valores, "ID");
+    let alfa: f64 = le_valor(&mut valores, "Alfa");
+    let num_vizinhos: u32 = le_valor(&mut valores, "NumVizinhos");
+
+    let mut grasp = Grasp::new(inst);
+    grasp.alfa(alfa)
+        .construcao(opcoes.construcao)
+        .num_vizinhos(num_vizinhos)
+        .max_iter(INF as u64)
+        .timeout(30)
+        .objetivo(opcoes.objetivo)
+        .temporizacao(opcoes.temporizacao)
+        .criterios(&opcoes.criterios)
+        .threads(opcoes.threads);
+
+    executa_experimento(&id, opcoes, &mut grasp, Grasp::semente);
+}
+
+fn experimento_ag(inst: &Instancia, opcoes: &Opcoes, config: &[&str]) {
+    let mut valores = le_config(config, "ag ID PopTam XoChance Cruz Mut MutChance").into_iter();
+    let id: String = le_valor(&mut valores, "ID");
+    let pop_tam: usize = le_valor(&mut valores, "PopTam");
+    let xo_chance: f64 = le_valor(&mut valores, "XoChance");
+    let cruz: u32 = le_valor(&mut valores, "Cruz");
+    let mutacao: u32 = le_valor(&mut valores, "Mut");
+    let mut_chance: f64 = le_valor(&mut valores, "MutChance");
 
     let cruz = match cruz {
         0 => Cruzamento::PMX,
@@ -118,40 +450,86 @@ fn experimento_ag(inst: Instancia, config: &[&str]) {
         }
     };
 
-    let mut ag = Ag::new(&inst);
+    let mut ag = Ag::new(inst);
     ag.pop_tam(pop_tam)
         .xo_chance(xo_chance)
         .cruz(cruz)
         .mutacao(mutacao)
         .mut_chance(mut_chance)
-        .timeout(30);
+        .timeout(30)
+        .objetivo(opcoes.objetivo)
+        .temporizacao(opcoes.temporizacao)
+        .criterios(&opcoes.criterios)
+        .threads(opcoes.threads);
 
-    println!("ID,iExec,FO,IterAlvo,TotalIter,Tempo");
-    for i in 0..NUM_EXEC {
-        let t = Instant::now();
-        let (solucao, iter_alvo, total_iter) = ag.solve();
-        let tempo = t.elapsed();
+    executa_experimento(&id, opcoes, &mut ag, Ag::semente);
+}
 
-        println!("{},{},{},{},{},{}.{}",
-                 id,
-                 i,
-                 solucao.fo(),
-                 iter_alvo,
-                 total_iter,
-                 tempo.as_secs(),
-                 tempo.subsec_nanos());
-    }
+fn experimento_tabu(inst: &Instancia, opcoes: &Opcoes, config: &[&str]) {
+    let mut valores = le_config(config, "tabu ID Tenure Diversificacao").into_iter();
+    let id: String = le_valor(&mut valores, "ID");
+    let tenure: u64 = le_valor(&mut valores, "Tenure");
+    let diversificacao: f64 = le_valor(&mut valores, "Diversificacao");
+
+    let mut tabu = Tabu::new(inst);
+    tabu.tenure(tenure)
+        .diversificacao(diversificacao)
+        .construcao(opcoes.construcao)
+        .timeout(30)
+        .objetivo(opcoes.objetivo)
+        .temporizacao(opcoes.temporizacao)
+        .criterios(&opcoes.criterios)
+        .threads(opcoes.threads);
+
+    executa_experimento(&id, opcoes, &mut tabu, Tabu::semente);
+}
+
+fn experimento_sa(inst: &Instancia, opcoes: &Opcoes, config: &[&str]) {
+    let mut valores = le_config(config, "sa ID Resfriamento Taxa IterTemperatura").into_iter();
+    let id: String = le_valor(&mut valores, "ID");
+    let resfriamento: u32 = le_valor(&mut valores, "Resfriamento");
+    let taxa: f64 = le_valor(&mut valores, "Taxa");
+    let iter_temperatura: u64 = le_valor(&mut valores, "IterTemperatura");
+
+    let resfriamento = match resfriamento {
+        0 => Resfriamento::Geometrico(taxa),
+        1 => Resfriamento::Adaptativo(taxa),
+        _ => {
+            println!("Resfriamento inválido");
+            process::exit(1);
+        }
+    };
+
+    let mut sa = Recozimento::new(inst);
+    sa.resfriamento(resfriamento)
+        .iter_temperatura(iter_temperatura)
+        .construcao(opcoes.construcao)
+        .timeout(30)
+        .objetivo(opcoes.objetivo)
+        .temporizacao(opcoes.temporizacao)
+        .criterios(&opcoes.criterios)
+        .threads(opcoes.threads);
+
+    executa_experimento(&id, opcoes, &mut sa, Recozimento::semente);
 }
 
-fn experimento(inst: Instancia) {
+fn experimento(inst: &Instancia, opcoes: &Opcoes) {
+    // O experimento só mostra o resumo de cada execução
+    if opcoes.solucao.is_some() || opcoes.relatorio.is_some() || opcoes.gantt.is_some() {
+        println!("--solucao, --relatorio e --gantt só valem para a execução de um algoritmo");
+        process::exit(1);
+    }
+
     let mut config = String::new();
 
     io::stdin().read_line(&mut config).expect("Erro ao ler configuração");
     let config: Vec<_> = config.split_whitespace().collect();
 
-    match config[0].as_ref() {
-        "grasp" => experimento_grasp(inst, &config[1..]),
-        "ag" => experimento_ag(inst, &config[1..]),
+    match config.first() {
+        Some(&"grasp") => experimento_grasp(inst, opcoes, &config[1..]),
+        Some(&"ag") => experimento_ag(inst, opcoes, &config[1..]),
+        Some(&"tabu") => experimento_tabu(inst, opcoes, &config[1..]),
+        Some(&"sa") => experimento_sa(inst, opcoes, &config[1..]),
         _ => {
             println!("Algoritmo inválido");
             process::exit(1);
@@ -159,9 +537,166 @@ fn experimento(inst: Instancia) {
     }
 }
 
+fn carrega(arquivo: &str, opcoes: &Opcoes) -> Result<Instancia, Erro> {
+    match opcoes.formato {
+        Formato::Padrao if documento::estruturado(arquivo) => documento::le_instancia(arquivo),
+        Formato::Padrao => Instancia::from_arquivo(arquivo),
+        Formato::Wt(n) => orlib::seleciona(orlib::le_wt(arquivo, n)?, opcoes.indice),
+        Formato::Sch(h) => orlib::seleciona(orlib::le_sch(arquivo, h)?, opcoes.indice),
+    }
+}
+
+// Escreve na saída padrão uma instância gerada com os parâmetros
+// N PMin PMax TF RDD [PesoMax]
+fn gerar(args: &[String], opcoes: &Opcoes) {
+    if args.len() != 5 && args.len() != 6 {
+        println!("Uso: ./escalonamento -gerar N PMin PMax TF RDD [PesoMax] [--seed N]");
+        process::exit(1);
+    }
+
+    let mut valores = args.iter().cloned();
+    let num_tarefas = le_valor(&mut valores, "N");
+    let duracao_min = le_valor(&mut valores, "PMin");
+    let duracao_max = le_valor(&mut valores, "PMax");
+    let tf = le_valor(&mut valores, "TF");
+    let rdd = le_valor(&mut valores, "RDD");
+    let peso_max = if args.len() == 6 {
+        le_valor(&mut valores, "PesoMax")
+    } else {
+        1
+    };
+
+    let semente = opcoes.semente(0);
+    let inst = Gerador::new(num_tarefas)
+        .duracao(duracao_min, duracao_max)
+        .tf(tf)
+        .rdd(rdd)
+        .peso_max(peso_max)
+        .semente(semente)
+        .gera()
+        .unwrap_or_else(|e| falha(e));
+
+    if let Some(ref arquivo) = opcoes.instancia {
+        documento::escreve_instancia(&inst, arquivo).unwrap_or_else(|e| falha(e));
+    } else {
+        let stdout = io::stdout();
+        let mut saida = stdout.lock();
+        inst.escreve(&mut saida)
+            .and_then(|_| saida.flush())
+            .expect("Erro ao escrever a instância");
+    }
+    eprintln!("Semente: {}", semente);
+}
+
 fn print_usage() {
     let usage = "
-    Experimento: ./escalonamento <entrada> -e
+    Gerador: ./escalonamento -gerar N PMin PMax TF RDD [PesoMax] [--seed N] > instancia
+             ./escalonamento -gerar N PMin PMax TF RDD [PesoMax] --instancia ARQ
+
+    Gera uma instância com N tarefas, durações uniformes em [PMin, PMax] e
+    entregas uniformes em [P(1 - TF - RDD/2), P(1 - TF + RDD/2)], em que P é a
+    soma das durações. Os pesos são uniformes em [1, PesoMax] (padrão 1).
+
+    Modelo MIP: ./escalonamento <entrada> -mip <modelo.lp|modelo.mps> [--otima]
+                                              [--inicio ARQ.mst|ARQ.sol]
+
+    Grava o modelo disjuntivo da soma ponderada de adiantamentos e atrasos no
+    formato LP do CPLEX ou MPS livre. Sem --otima, o modelo não permite
+    ociosidade. Com --inicio, a solução do GRASP (com os mesmos critérios de
+    parada de -grasp) é gravada como solução inicial, no formato MST do CPLEX
+    ou em texto com o nome e o valor de cada variável.
+
+    Verificação: ./escalonamento <entrada> -verificar <solucao> [--otima] [--objetivo NOME]
+
+    Confere se a solução é uma permutação das tarefas e se a FO informada é
+    a da sequência, mostrando o custo de cada tarefa. Termina com código 1 se
+    a solução não confere. A solução pode ser um arquivo .json ou .toml
+    gravado por --solucao, ou um arquivo de texto com a FO seguida dos ids.
+
+    Execução: ./escalonamento <entrada> -neh|-grasp|-ag|-tabu|-sa|-bb|-pd [opções]
+                                        [--solucao ARQ] [--relatorio tabela|csv|json]
+                                        [--gantt ARQ.svg]
+
+    Executa o algoritmo uma vez e mostra a solução. As demais opções são as
+    mesmas do experimento.
+
+    Experimento: ./escalonamento <entrada> -e [--otima] [--objetivo NOME] [--seed N]
+                                              [--timeout-ms MS] [--max-iter N]
+                                              [--max-iter-total N] [--max-avaliacoes N]
+                                              [--alvo FO] [--threads N]
+                                              [--wt N | --sch H] [--indice K]
+                                              [--construcao neh|v]
+
+    Com --otima, cada sequência é avaliada com inserção ótima de ociosidade
+
+    Com --seed N, a i-ésima execução usa a semente N + i, e os resultados
+    podem ser reproduzidos. Sem a opção, as sementes são aleatórias.
+
+    Critérios de parada (substituem os padrões; a execução para assim que
+    qualquer um é satisfeito):
+        --timeout-ms MS        tempo limite em milissegundos
+        --max-iter N           iterações consecutivas sem melhoria
+        --max-iter-total N     total de iterações
+        --max-avaliacoes N     avaliações da função objetivo
+        --alvo FO              para ao alcançar uma FO menor ou igual a FO
+
+    Com --wt N, a entrada é um arquivo wt40/wt50/wt100 da OR-Library com
+    instâncias de N tarefas, e --indice K escolhe a K-ésima (padrão 1). Os
+    pesos são de atraso, então o objetivo et equivale ao atraso ponderado.
+    Com --sch H, a entrada é um arquivo sch da OR-Library (Biskup e Feldmann),
+    com entrega comum floor(H * soma das durações).
+
+    Entradas com extensão .json ou .toml são lidas no formato estruturado
+    descrito no README. Com --solucao ARQ (.json ou .toml), a execução de um
+    algoritmo grava a solução, com os tempos de cada tarefa e os parâmetros.
+    Com --instancia ARQ (.json ou .toml), a instância lida ou gerada por
+    -gerar é gravada nesse formato.
+
+    Com --relatorio tabela, csv ou json, a execução de um algoritmo mostra o
+    início, o fim, a entrega, o adiantamento e o atraso de cada tarefa da
+    solução, com os totais e o número de tarefas adiantadas, no prazo e
+    atrasadas.
+
+    Com --gantt ARQ, a execução de um algoritmo grava o diagrama de Gantt da
+    solução em SVG, com as entregas marcadas e as tarefas coloridas pelo
+    adiantamento ou atraso.
+
+    -tabu executa a busca tabu sobre as vizinhanças completas de inserção,
+    swap e swap de pares adjacentes, com tenure 10 e diversificação 0.1.
+
+    -sa executa o recozimento simulado com resfriamento geométrico (0.95 a
+    cada nível de 1000 movimentos), temperatura inicial estimada para aceitar
+    80% das pioras médias e reaquecimento após 20 níveis sem melhoria.
+    --max-iter conta níveis de temperatura sem melhoria.
+
+    -bb executa o branch-and-bound exato (objetivo et, temporização semiativa,
+    sem liberação nem setup), partindo da solução de até 1s de GRASP, que
+    conta no tempo limite e nas avaliações. O tempo limite padrão é de 60s;
+    se ele for atingido, são mostrados o limite inferior e o gap.
+
+    -pd executa a programação dinâmica sobre subconjuntos, exata para
+    qualquer objetivo sem ociosidade (com --otima, só objetivos regulares),
+    sem liberação nem setup. Instâncias com mais de 25 tarefas são recusadas
+    pela memória necessária.
+
+    --construcao escolhe a construção do GRASP e da solução inicial da busca
+    tabu e do recozimento: neh (padrão) ou v, baseada na forma em V das
+    soluções ótimas com entrega comum.
+
+    Com --threads N, cada execução do GRASP, do AG, da busca tabu ou do
+    recozimento roda N buscas independentes em paralelo, que compartilham a
+    melhor solução. Os limites de avaliações e de iterações totais são
+    divididos entre as threads.
+    --threads 0 usa todos os núcleos.
+
+    Objetivos (--objetivo):
+        et                   soma ponderada de adiantamentos e atrasos (padrão)
+        atraso               soma dos atrasos
+        atraso-ponderado     soma dos atrasos ponderados
+        lmax                 lateness máximo
+        atrasadas            número de tarefas atrasadas
+        conclusao-ponderada  soma ponderada dos tempos de finalização
+        makespan             instante de finalização da última tarefa
 
     A configuração será lida da entrada padrão
 
@@ -173,28 +708,35 @@ fn print_usage() {
     Onde
         Cruz = 0 (PMX) ou 1 (OX)
         Mut = 0 (Swap) ou 1 (2-opt)
+
+    Formato da configuração da busca tabu:
+        tabu ID Tenure Diversificacao
+
+    Formato da configuração do recozimento simulado:
+        sa ID Resfriamento Taxa IterTemperatura
+    Onde
+        Resfriamento = 0 (geométrico, Taxa = alfa) ou 1 (adaptativo, Taxa = lambda)
     ";
 
     println!("{}", usage);
 }
 
-fn teste_neh(inst: &Instancia) {
-    let s = busca(inst);
-    println!("{:?}", s.sequencia());
-    println!("FO: {}", s.fo());
-}
-
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let (args, opcoes) = le_opcoes(env::args());
+
+    if args.len() > 1 && args[1] == "-gerar" {
+        gerar(&args[2..], &opcoes);
+        return;
+    }
 
     let inst: Instancia = match args.len() {
         1 => Instancia::toy(),
-        2...3 => {
+        2..=4 => {
             if args[1] == "-h" {
                 print_usage();
                 process::exit(0);
             } else {
-                Instancia::from_arquivo(&args[1])
+                carrega(&args[1], &opcoes).unwrap_or_else(|e| falha(e))
             }
         }
         _ => {
@@ -203,20 +745,31 @@ fn main() {
         }
     };
 
+    if let Some(ref arquivo) = opcoes.instancia {
+        documento::escreve_instancia(&inst, arquivo).unwrap_or_else(|e| falha(e));
+    }
+
+    let av = opcoes.avaliador(&inst);
     match args.len() {
-        2 => teste(inst),
+        2 => teste(&av, &opcoes),
         3 => {
-            match args[2].as_ref() {
-                "-neh" => teste_neh(&inst),
-                "-grasp" => teste_grasp(&inst),
-                "-ag" => teste_ag(&inst),
-                "-e" => experimento(inst),
+            match args[2].as_str() {
+                "-neh" => teste_neh(&av, &opcoes),
+                "-grasp" => teste_grasp(&av, &opcoes),
+                "-ag" => teste_ag(&av, &opcoes),
+                "-tabu" => teste_tabu(&av, &opcoes),
+                "-sa" => teste_sa(&av, &opcoes),
+                "-bb" => teste_bb(&av, &opcoes),
+                "-pd" => teste_pd(&av, &opcoes),
+                "-e" => experimento(&inst, &opcoes),
                 _ => {
                     println!("Algoritmo inválido");
                     process::exit(1);
                 }
             }
         }
-        _ => println!("Escolha um algoritmo (-grasp ou -ag)"),
+        4 if args[2] == "-verificar" => verificar(&av, &args[3]),
+        4 if args[2] == "-mip" => exporta_mip(&av, &opcoes, &args[3]),
+        _ => println!("Escolha um algoritmo (-grasp, -ag, -tabu, -sa, -bb ou -pd)"),
     }
 }
\ No newline at end of file
@@ -0,0 +1,387 @@
+use std::cmp::max;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use erro::Erro;
+use instancia::{Instancia, IdTarefa, Sequencia, Temporizacao};
+use objetivo::{Objetivo, AdiantamentoAtraso};
+
+// Modelo de programação inteira mista disjuntivo (big-M) da soma ponderada
+// de adiantamentos e atrasos, para resolvedores externos:
+//
+//   min  soma_j (a_j adi_j + b_j atr_j)
+//   s.a. C_j + adi_j - atr_j = d_j                    para todo j
+//        C_j - C_i - M y_i_j >= p_j + s_ij - M        para i < j
+//        C_i - C_j + M y_i_j >= p_i + s_ji            para i < j
+//        r_j + p_j <= C_j <= H
+//        y_i_j binária: 1 se i vem antes de j
+//
+// Com a temporização ótima, H limita o fim de todas as tarefas numa solução
+// ótima. Com a semiativa, sem ociosidade, H é a soma das durações.
+//
+// As restrições de sequência valem para todo par, não só para tarefas
+// vizinhas, então os setups precisam satisfazer s_ik <= s_ij + p_j + s_jk;
+// caso contrário o modelo cortaria sequências factíveis.
+
+// Trio (i, j, k) em que ir de i a k passando por j leva menos tempo que o
+// setup direto de i para k
+fn viola_triangular(inst: &Instancia) -> Option<(IdTarefa, IdTarefa, IdTarefa)> {
+    let n = inst.num_tarefas();
+    let s = |i: IdTarefa, j: IdTarefa| inst.setup(Some(i), j) as i64;
+    for i in 0..n {
+        for j in (0..n).filter(|&j| j != i) {
+            for k in (0..n).filter(|&k| k != i && k != j) {
+                if s(i, k) > s(i, j) + inst.tarefa(j).duracao() as i64 + s(j, k) {
+                    return Some((i, j, k));
+                }
+            }
+        }
+    }
+    None
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Sentido {
+    Igual,
+    MaiorIgual,
+}
+
+struct Variavel {
+    nome: String,
+    inferior: i64,
+    superior: Option<i64>,
+    custo: i64,
+    binaria: bool,
+}
+
+struct Restricao {
+    nome: String,
+    termos: Vec<(usize, i64)>,
+    sentido: Sentido,
+    rhs: i64,
+}
+
+pub struct Modelo {
+    num_tarefas: usize,
+    // Variáveis na ordem C, adi, atr (cada uma indexada pela tarefa) e y
+    variaveis: Vec<Variavel>,
+    restricoes: Vec<Restricao>,
+}
+
+impl Modelo {
+    pub fn new(inst: &Instancia,
+               objetivo: &dyn Objetivo,
+               temporizacao: Temporizacao)
+               -> Result<Modelo, Erro> {
+        let nao_suporta = |motivo: String| {
+            Err(Erro::NaoSuportado {
+                algoritmo: "O modelo MIP",
+                motivo,
+            })
+        };
+        let n = inst.num_tarefas();
+        let tarefa = |j: IdTarefa| inst.tarefa(j);
+        let com_liberacao = (0..n).any(|j| tarefa(j).liberacao() > 0);
+
+        if objetivo.nome() != AdiantamentoAtraso.nome() {
+            return nao_suporta(format!("o objetivo {}", objetivo.nome()));
+        }
+        if temporizacao == Temporizacao::Semiativa && (inst.tem_setup() || com_liberacao) {
+            return nao_suporta("liberação ou setup sem ociosidade (use a temporização ótima)"
+                .to_string());
+        }
+        // A verificação é O(n³) e só importa com setups
+        let violacao = if inst.tem_setup() { viola_triangular(inst) } else { None };
+        if let Some((i, j, k)) = violacao {
+            return nao_suporta(format!("setups fora da desigualdade triangular (de {} para {} \
+                                        passando por {} é mais rápido que o setup direto)",
+                                       i,
+                                       k,
+                                       j));
+        }
+
+        let soma_duracoes: i64 = (0..n).map(|j| tarefa(j).duracao() as i64).sum();
+        let max_setup = |j: IdTarefa| (0..n).map(|i| inst.setup(Some(i), j) as i64).max().unwrap_or(0);
+        let horizonte = match temporizacao {
+            Temporizacao::Semiativa => soma_duracoes,
+            Temporizacao::Otima => {
+                // Depois da última liberação e da última entrega, não há
+                // motivo para ociosidade
+                let inicio = (0..n)
+                    .map(|j| max(tarefa(j).liberacao(), tarefa(j).entrega()) as i64)
+                    .max()
+                    .unwrap_or(0);
+                inicio + soma_duracoes + (0..n).map(max_setup).sum::<i64>()
+            }
+        };
+        let big_m = horizonte + (0..n).map(max_setup).max().unwrap_or(0);
+
+        let mut variaveis = vec![];
+        for j in 0..n {
+            variaveis.push(Variavel {
+                nome: format!("C_{}", j),
+                inferior: (tarefa(j).liberacao() + tarefa(j).duracao()) as i64,
+                superior: Some(horizonte),
+                custo: 0,
+                binaria: false,
+            });
+        }
+        for j in 0..n {
+            variaveis.push(Variavel {
+                nome: format!("adi_{}", j),
+                inferior: 0,
+                superior: None,
+                custo: tarefa(j).peso_adiantamento() as i64,
+                binaria: false,
+            });
+        }
+        for j in 0..n {
+            variaveis.push(Variavel {
+                nome: format!("atr_{}", j),
+                inferior: 0,
+                superior: None,
+                custo: tarefa(j).peso_atraso() as i64,
+                binaria: false,
+            });
+        }
+
+        let mut restricoes = vec![];
+        for j in 0..n {
+            restricoes.push(Restricao {
+                nome: format!("ent_{}", j),
+                termos: vec![(j, 1), (n + j, 1), (2 * n + j, -1)],
+                sentido: Sentido::Igual,
+                rhs: tarefa(j).entrega() as i64,
+            });
+        }
+        for i in 0..n {
+            for j in i + 1..n {
+                let y = variaveis.len();
+                variaveis.push(Variavel {
+                    nome: format!("y_{}_{}", i, j),
+                    inferior: 0,
+                    superior: Some(1),
+                    custo: 0,
+                    binaria: true,
+                });
+                restricoes.push(Restricao {
+                    nome: format!("seq_{}_{}", i, j),
+                    termos: vec![(j, 1), (i, -1), (y, -big_m)],
+                    sentido: Sentido::MaiorIgual,
+                    rhs: (tarefa(j).duracao() + inst.setup(Some(i), j)) as i64 - big_m,
+                });
+                restricoes.push(Restricao {
+                    nome: format!("seq_{}_{}", j, i),
+                    termos: vec![(i, 1), (j, -1), (y, big_m)],
+                    sentido: Sentido::MaiorIgual,
+                    rhs: (tarefa(i).duracao() + inst.setup(Some(j), i)) as i64,
+                });
+            }
+        }
+
+        Ok(Modelo {
+            num_tarefas: n,
+            variaveis,
+            restricoes,
+        })
+    }
+
+    pub fn num_variaveis(&self) -> usize {
+        self.variaveis.len()
+    }
+
+    pub fn num_restricoes(&self) -> usize {
+        self.restricoes.len()
+    }
+
+    // Formato LP do CPLEX
+    pub fn escreve_lp<W: Write>(&self, saida: &mut W) -> io::Result<()> {
+        let termo = |coef: i64, var: usize| {
+            let sinal = if coef < 0 { '-' } else { '+' };
+            format!(" {} {} {}", sinal, coef.abs(), self.variaveis[var].nome)
+        };
+
+        writeln!(saida,
+                 "\\ Soma ponderada de adiantamentos e atrasos, {} tarefas",
+                 self.num_tarefas)?;
+        writeln!(saida, "Minimize")?;
+        writeln!(saida, " obj:")?;
+        for (k, v) in self.variaveis.iter().enumerate().filter(|&(_, v)| v.custo != 0) {
+            writeln!(saida, "{}", termo(v.custo, k))?;
+        }
+
+        writeln!(saida, "Subject To")?;
+        for r in &self.restricoes {
+            let termos: String = r.termos.iter().map(|&(var, coef)| termo(coef, var)).collect();
+            let sentido = match r.sentido {
+                Sentido::Igual => "=",
+                Sentido::MaiorIgual => ">=",
+            };
+            writeln!(saida, " {}:{} {} {}", r.nome, termos, sentido, r.rhs)?;
+        }
+
+        writeln!(saida, "Bounds")?;
+        for v in self.variaveis.iter().filter(|v| !v.binaria) {
+            match v.superior {
+                Some(superior) => writeln!(saida, " {} <= {} <= {}", v.inferior, v.nome, superior)?,
+                None => writeln!(saida, " {} >= {}", v.nome, v.inferior)?,
+            }
+        }
+
+        writeln!(saida, "Binaries")?;
+        for v in self.variaveis.iter().filter(|v| v.binaria) {
+            writeln!(saida, " {}", v.nome)?;
+        }
+        writeln!(saida, "End")
+    }
+
+    // Formato MPS livre (campos separados por espaços)
+    pub fn escreve_mps<W: Write>(&self, saida: &mut W) -> io::Result<()> {
+        writeln!(saida, "NAME escalonamento")?;
+        writeln!(saida, "ROWS")?;
+        writeln!(saida, " N obj")?;
+        for r in &self.restricoes {
+            let sentido = match r.sentido {
+                Sentido::Igual => 'E',
+                Sentido::MaiorIgual => 'G',
+            };
+            writeln!(saida, " {} {}", sentido, r.nome)?;
+        }
+
+        // Coeficientes por coluna
+        let mut colunas = vec![vec![]; self.variaveis.len()];
+        for r in &self.restricoes {
+            for &(var, coef) in &r.termos {
+                colunas[var].push((r.nome.as_str(), coef));
+            }
+        }
+
+        writeln!(saida, "COLUMNS")?;
+        let mut inteiras = false;
+        for (k, v) in self.variaveis.iter().enumerate() {
+            if v.binaria != inteiras {
+                let marca = if v.binaria { "INTORG" } else { "INTEND" };
+                writeln!(saida, " MARKER 'MARKER' '{}'", marca)?;
+                inteiras = v.binaria;
+            }
+            if v.custo != 0 {
+                writeln!(saida, " {} obj {}", v.nome, v.custo)?;
+            }
+            for &(linha, coef) in &colunas[k] {
+                writeln!(saida, " {} {} {}", v.nome, linha, coef)?;
+            }
+        }
+        if inteiras {
+            writeln!(saida, " MARKER 'MARKER' 'INTEND'")?;
+        }
+
+        writeln!(saida, "RHS")?;
+        for r in self.restricoes.iter().filter(|r| r.rhs != 0) {
+            writeln!(saida, " RHS {} {}", r.nome, r.rhs)?;
+        }
+
+        writeln!(saida, "BOUNDS")?;
+        for v in &self.variaveis {
+            if v.binaria {
+                writeln!(saida, " BV BND {}", v.nome)?;
+                continue;
+            }
+            if v.inferior != 0 {
+                writeln!(saida, " LO BND {} {}", v.nome, v.inferior)?;
+            }
+            if let Some(superior) = v.superior {
+                writeln!(saida, " UP BND {} {}", v.nome, superior)?;
+            }
+        }
+        writeln!(saida, "ENDATA")
+    }
+
+    // Valores das variáveis, na ordem do modelo, para a sequência com os
+    // instantes de finalização `fim` (indexados pelo id da tarefa)
+    pub fn valores(&self, inst: &Instancia, sequencia: &Sequencia, fim: &[i32]) -> Vec<i64> {
+        let n = self.num_tarefas;
+        let mut posicao = vec![0; n];
+        for (k, &j) in sequencia.iter().enumerate() {
+            posicao[j] = k;
+        }
+
+        let mut valores: Vec<i64> = fim.iter().map(|&f| f as i64).collect();
+        valores.extend((0..n).map(|j| max(0, inst.tarefa(j).entrega() - fim[j]) as i64));
+        valores.extend((0..n).map(|j| max(0, fim[j] - inst.tarefa(j).entrega()) as i64));
+        for i in 0..n {
+            for j in i + 1..n {
+                valores.push(if posicao[i] < posicao[j] { 1 } else { 0 });
+            }
+        }
+        valores
+    }
+
+    // Solução inicial no formato MST do CPLEX
+    pub fn escreve_mst<W: Write>(&self, saida: &mut W, valores: &[i64], fo: i32) -> io::Result<()> {
+        writeln!(saida, "<?xml version = \"1.0\" standalone=\"yes\"?>")?;
+        writeln!(saida, "<CPLEXSolutions version=\"1.2\">")?;
+        writeln!(saida, " <CPLEXSolution version=\"1.2\">")?;
+        writeln!(saida,
+                 "  <header problemName=\"escalonamento\" solutionName=\"m1\" objectiveValue=\"{}\"/>",
+                 fo)?;
+        writeln!(saida, "  <variables>")?;
+        for (k, (v, valor)) in self.variaveis.iter().zip(valores).enumerate() {
+            writeln!(saida,
+                     "   <variable name=\"{}\" index=\"{}\" value=\"{}\"/>",
+                     v.nome,
+                     k,
+                     valor)?;
+        }
+        writeln!(saida, "  </variables>")?;
+        writeln!(saida, " </CPLEXSolution>")?;
+        writeln!(saida, "</CPLEXSolutions>")
+    }
+
+    // Solução inicial em texto, uma variável por linha: nome e valor
+    pub fn escreve_sol<W: Write>(&self, saida: &mut W, valores: &[i64], fo: i32) -> io::Result<()> {
+        writeln!(saida, "# Valor da função objetivo = {}", fo)?;
+        for (v, valor) in self.variaveis.iter().zip(valores) {
+            writeln!(saida, "{} {}", v.nome, valor)?;
+        }
+        Ok(())
+    }
+}
+
+fn extensao(arquivo: &str) -> Option<&str> {
+    Path::new(arquivo).extension().and_then(|e| e.to_str())
+}
+
+fn grava<F>(arquivo: &str, escreve: F) -> Result<(), Erro>
+    where F: FnOnce(&mut BufWriter<File>) -> io::Result<()>
+{
+    let mut saida = BufWriter::new(File::create(arquivo).map_err(|e| Erro::escrita(arquivo, e))?);
+    escreve(&mut saida).and_then(|_| saida.flush()).map_err(|e| Erro::escrita(arquivo, e))
+}
+
+// Grava o modelo em LP ou MPS, de acordo com a extensão
+pub fn escreve_modelo(modelo: &Modelo, arquivo: &str) -> Result<(), Erro> {
+    match extensao(arquivo) {
+        Some("lp") => grava(arquivo, |saida| modelo.escreve_lp(saida)),
+        Some("mps") => grava(arquivo, |saida| modelo.escreve_mps(saida)),
+        _ => {
+            Err(Erro::ExtensaoDesconhecida {
+                arquivo: arquivo.to_string(),
+                aceitas: ".lp ou .mps",
+            })
+        }
+    }
+}
+
+// Grava a solução inicial em MST ou em texto, de acordo com a extensão
+pub fn escreve_inicio(modelo: &Modelo, valores: &[i64], fo: i32, arquivo: &str) -> Result<(), Erro> {
+    match extensao(arquivo) {
+        Some("mst") => grava(arquivo, |saida| modelo.escreve_mst(saida, valores, fo)),
+        Some("sol") => grava(arquivo, |saida| modelo.escreve_sol(saida, valores, fo)),
+        _ => {
+            Err(Erro::ExtensaoDesconhecida {
+                arquivo: arquivo.to_string(),
+                aceitas: ".mst ou .sol",
+            })
+        }
+    }
+}
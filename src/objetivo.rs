@@ -0,0 +1,156 @@
+use std::cmp::max;
+use erro::Erro;
+use instancia::Tarefa;
+
+// Como os custos das tarefas são combinados na função objetivo.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Agregacao {
+    Soma,
+    Maximo,
+}
+
+pub trait Objetivo: Sync {
+    fn nome(&self) -> &'static str;
+
+    // Custo da tarefa quando ela termina no instante `fim`
+    fn custo(&self, tarefa: &Tarefa, fim: i32) -> i32;
+
+    fn agregacao(&self) -> Agregacao {
+        Agregacao::Soma
+    }
+
+    // Objetivos regulares nunca melhoram atrasando uma tarefa, então não há
+    // motivo para inserir ociosidade.
+    fn regular(&self) -> bool {
+        true
+    }
+}
+
+// Combina os custos de cada par (tarefa, instante de finalização)
+pub fn agrega<'a, I>(objetivo: &dyn Objetivo, tarefas: I) -> i32
+    where I: Iterator<Item = (&'a Tarefa, i32)>
+{
+    let custos = tarefas.map(|(t, fim)| objetivo.custo(t, fim));
+    match objetivo.agregacao() {
+        Agregacao::Soma => custos.sum(),
+        Agregacao::Maximo => custos.max().unwrap_or(0),
+    }
+}
+
+// Soma ponderada de adiantamentos e atrasos
+pub struct AdiantamentoAtraso;
+
+impl Objetivo for AdiantamentoAtraso {
+    fn nome(&self) -> &'static str {
+        "et"
+    }
+
+    fn custo(&self, tarefa: &Tarefa, fim: i32) -> i32 {
+        tarefa.custo(fim)
+    }
+
+    fn regular(&self) -> bool {
+        false
+    }
+}
+
+// Soma dos atrasos
+pub struct Atraso;
+
+impl Objetivo for Atraso {
+    fn nome(&self) -> &'static str {
+        "atraso"
+    }
+
+    fn custo(&self, tarefa: &Tarefa, fim: i32) -> i32 {
+        max(0, fim - tarefa.entrega())
+    }
+}
+
+// Soma dos atrasos ponderados pelo peso de atraso
+pub struct AtrasoPonderado;
+
+impl Objetivo for AtrasoPonderado {
+    fn nome(&self) -> &'static str {
+        "atraso-ponderado"
+    }
+
+    fn custo(&self, tarefa: &Tarefa, fim: i32) -> i32 {
+        tarefa.peso_atraso() * max(0, fim - tarefa.entrega())
+    }
+}
+
+// Lmax: maior diferença entre a finalização e a entrega (pode ser negativa)
+pub struct LatenessMaximo;
+
+impl Objetivo for LatenessMaximo {
+    fn nome(&self) -> &'static str {
+        "lmax"
+    }
+
+    fn custo(&self, tarefa: &Tarefa, fim: i32) -> i32 {
+        fim - tarefa.entrega()
+    }
+
+    fn agregacao(&self) -> Agregacao {
+        Agregacao::Maximo
+    }
+}
+
+// Número de tarefas atrasadas
+pub struct NumeroAtrasadas;
+
+impl Objetivo for NumeroAtrasadas {
+    fn nome(&self) -> &'static str {
+        "atrasadas"
+    }
+
+    fn custo(&self, tarefa: &Tarefa, fim: i32) -> i32 {
+        if fim > tarefa.entrega() { 1 } else { 0 }
+    }
+}
+
+// Soma dos tempos de finalização ponderados pelo peso de atraso
+pub struct ConclusaoPonderada;
+
+impl Objetivo for ConclusaoPonderada {
+    fn nome(&self) -> &'static str {
+        "conclusao-ponderada"
+    }
+
+    fn custo(&self, tarefa: &Tarefa, fim: i32) -> i32 {
+        tarefa.peso_atraso() * fim
+    }
+}
+
+// Instante de finalização da última tarefa
+pub struct Makespan;
+
+impl Objetivo for Makespan {
+    fn nome(&self) -> &'static str {
+        "makespan"
+    }
+
+    fn custo(&self, _: &Tarefa, fim: i32) -> i32 {
+        fim
+    }
+
+    fn agregacao(&self) -> Agregacao {
+        Agregacao::Maximo
+    }
+}
+
+pub const OBJETIVOS: [&'static dyn Objetivo; 7] = [&AdiantamentoAtraso,
+                                                   &Atraso,
+                                                   &AtrasoPonderado,
+                                                   &LatenessMaximo,
+                                                   &NumeroAtrasadas,
+                                                   &ConclusaoPonderada,
+                                                   &Makespan];
+
+pub fn from_nome(nome: &str) -> Result<&'static dyn Objetivo, Erro> {
+    OBJETIVOS.iter()
+        .cloned()
+        .find(|o| o.nome() == nome)
+        .ok_or_else(|| Erro::ObjetivoInvalido(nome.to_string()))
+}
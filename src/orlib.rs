@@ -0,0 +1,224 @@
+use std::io::BufRead;
+use erro::{Erro, Problema, verifica_parametro};
+use instancia::{Instancia, Tarefa, abre, le_linhas, le_numeros};
+
+// Números de `leitor` em ordem, cada um com a linha em que aparece
+fn le_sequencia<R: BufRead>(arquivo: &str, leitor: R) -> Result<Vec<(usize, i32)>, Erro> {
+    let mut numeros = vec![];
+    for (linha, texto) in le_linhas(arquivo, leitor)? {
+        let valores = le_numeros(&texto).map_err(|p| Erro::formato(arquivo, linha, p))?;
+        numeros.extend(valores.into_iter().map(|v| (linha, v)));
+    }
+    Ok(numeros)
+}
+
+// Lê um arquivo wt40/wt50/wt100 da OR-Library, com várias instâncias de
+// atraso ponderado de `num_tarefas` tarefas cada. Cada instância é formada
+// pelas durações, depois pelos pesos e por fim pelas entregas. Os pesos
+// viram pesos de atraso, e os de adiantamento são nulos, então a soma de
+// adiantamentos e atrasos é o atraso ponderado.
+pub fn le_wt(arquivo: &str, num_tarefas: usize) -> Result<Vec<Instancia>, Erro> {
+    le_wt_de(arquivo, abre(arquivo)?, num_tarefas)
+}
+
+// Como `le_wt`, mas lendo de `leitor`; `arquivo` só é usado nos erros
+pub fn le_wt_de<R: BufRead>(arquivo: &str,
+                            leitor: R,
+                            num_tarefas: usize)
+                            -> Result<Vec<Instancia>, Erro> {
+    verifica_parametro("num_tarefas", num_tarefas, num_tarefas > 0)?;
+    let numeros = le_sequencia(arquivo, leitor)?;
+    let tam = 3 * num_tarefas;
+
+    let mut instancias = vec![];
+    for bloco in numeros.chunks(tam) {
+        if bloco.len() < tam {
+            return Err(Erro::formato(arquivo,
+                                     bloco[bloco.len() - 1].0,
+                                     Problema::NumerosFaltando {
+                                         esperados: tam,
+                                         lidos: bloco.len(),
+                                     }));
+        }
+
+        let (duracoes, resto) = bloco.split_at(num_tarefas);
+        let (pesos, entregas) = resto.split_at(num_tarefas);
+        if let Some(&(linha, p)) = duracoes.iter().find(|&&(_, p)| p < 0) {
+            return Err(Erro::formato(arquivo, linha, Problema::DuracaoNegativa(p)));
+        }
+
+        let tarefas = duracoes.iter()
+            .zip(pesos)
+            .zip(entregas)
+            .map(|((&(_, p), &(_, w)), &(_, d))| Tarefa::com_pesos(p, d, 0, w))
+            .collect();
+        instancias.push(Instancia::new(tarefas));
+    }
+
+    Ok(instancias)
+}
+
+// Lê um arquivo sch10/sch20/.../sch1000 da OR-Library (Biskup e Feldmann):
+// o número de instâncias e, para cada uma, o número de tarefas seguido de
+// duração, peso de adiantamento e peso de atraso de cada tarefa. Todas as
+// tarefas têm a entrega comum d = floor(h * soma das durações), em que `h` é
+// o fator de restrição (0.2, 0.4, 0.6 e 0.8 na literatura).
+pub fn le_sch(arquivo: &str, h: f64) -> Result<Vec<Instancia>, Erro> {
+    le_sch_de(arquivo, abre(arquivo)?, h)
+}
+
+// Como `le_sch`, mas lendo de `leitor`; `arquivo` só é usado nos erros
+pub fn le_sch_de<R: BufRead>(arquivo: &str, leitor: R, h: f64) -> Result<Vec<Instancia>, Erro> {
+    verifica_parametro("h", h, h > 0.0 && h <= 1.0)?;
+    let numeros = le_sequencia(arquivo, leitor)?;
+    let mut numeros = numeros.iter().cloned();
+
+    let mut proximo = |esperados: usize, lidos: usize, linha: usize| {
+        numeros.next().ok_or_else(|| {
+            Erro::formato(arquivo,
+                          linha,
+                          Problema::NumerosFaltando {
+                              esperados,
+                              lidos,
+                          })
+        })
+    };
+
+    let (linha, k) = proximo(1, 0, 1)?;
+    if k < 0 {
+        return Err(Erro::formato(arquivo, linha, Problema::NumeroTarefas(k.to_string())));
+    }
+
+    // As contagens vêm do arquivo e não dimensionam os vetores
+    let mut instancias = vec![];
+    let mut ultima = linha;
+    for _ in 0..k {
+        let (linha, n) = proximo(1, 0, ultima)?;
+        if n < 0 {
+            return Err(Erro::formato(arquivo, linha, Problema::NumeroTarefas(n.to_string())));
+        }
+
+        let n = n as usize;
+        let mut dados = vec![];
+        ultima = linha;
+        for i in 0..3 * n {
+            let (linha, valor) = proximo(3 * n, i, ultima)?;
+            ultima = linha;
+            dados.push((linha, valor));
+        }
+
+        if let Some(&(linha, p)) = dados.iter().step_by(3).find(|&&(_, p)| p < 0) {
+            return Err(Erro::formato(arquivo, linha, Problema::DuracaoNegativa(p)));
+        }
+
+        let total = dados.iter().step_by(3).map(|&(_, p)| p as f64).sum::<f64>();
+        let entrega = (h * total).floor() as i32;
+        let tarefas = dados.chunks(3)
+            .map(|t| Tarefa::com_pesos(t[0].1, entrega, t[1].1, t[2].1))
+            .collect();
+        instancias.push(Instancia::new(tarefas));
+    }
+
+    if let Some((linha, _)) = numeros.next() {
+        return Err(Erro::formato(arquivo, linha, Problema::LinhaExtra));
+    }
+
+    Ok(instancias)
+}
+
+// Instância de número `indice` (a partir de 1, como na OR-Library)
+pub fn seleciona(mut instancias: Vec<Instancia>, indice: usize) -> Result<Instancia, Erro> {
+    verifica_parametro("indice",
+                       indice,
+                       indice >= 1 && indice <= instancias.len())?;
+    Ok(instancias.swap_remove(indice - 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Duas instâncias de 3 tarefas: durações, pesos e entregas, quebradas em
+    // linhas como nos arquivos da OR-Library
+    const WT: &str = " 5 2 7\n 1 4 3 10 6\n 9\n 1 1 2 2 3 3 8 4 12\n";
+
+    // Tuplas (duração, entrega, peso de adiantamento, peso de atraso)
+    fn tarefas(inst: &Instancia) -> Vec<(i32, i32, i32, i32)> {
+        (0..inst.num_tarefas())
+            .map(|id| {
+                let t = inst.tarefa(id);
+                (t.duracao(), t.entrega(), t.peso_adiantamento(), t.peso_atraso())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn le_wt_por_blocos() {
+        let instancias = le_wt_de("wt3.txt", WT.as_bytes(), 3).unwrap();
+        assert_eq!(instancias.len(), 2);
+        assert_eq!(tarefas(&instancias[0]), [(5, 10, 0, 1), (2, 6, 0, 4), (7, 9, 0, 3)]);
+        assert_eq!(tarefas(&instancias[1]), [(1, 8, 0, 2), (1, 4, 0, 3), (2, 12, 0, 3)]);
+
+        match le_wt_de("wt4.txt", WT.as_bytes(), 4) {
+            Err(Erro::Formato { linha, problema, .. }) => {
+                assert_eq!(linha, 4);
+                assert_eq!(problema,
+                           Problema::NumerosFaltando {
+                               esperados: 12,
+                               lidos: 6,
+                           });
+            }
+            _ => panic!("Instância incompleta aceita"),
+        }
+    }
+
+    // Duas instâncias, com 3 e 2 tarefas de (duração, adiantamento, atraso)
+    const SCH: &str = "2\n 3\n 4 1 2\n 2 3 1\n 6 1 1\n 2\n 5 2 2\n 3 1 4\n";
+
+    #[test]
+    fn le_sch_com_entrega_comum() {
+        // d = floor(0.5 * 12) e floor(0.5 * 8)
+        let instancias = le_sch_de("sch.txt", SCH.as_bytes(), 0.5).unwrap();
+        assert_eq!(instancias.len(), 2);
+        assert_eq!(tarefas(&instancias[0]), [(4, 6, 1, 2), (2, 6, 3, 1), (6, 6, 1, 1)]);
+        assert_eq!(tarefas(&instancias[1]), [(5, 4, 2, 2), (3, 4, 1, 4)]);
+
+        // floor(0.3 * 12) = 3
+        let instancias = le_sch_de("sch.txt", SCH.as_bytes(), 0.3).unwrap();
+        assert_eq!(instancias[0].tarefa(0).entrega(), 3);
+
+        let erro = |texto: &str| match le_sch_de("sch.txt", texto.as_bytes(), 0.5) {
+            Err(Erro::Formato { linha, problema, .. }) => (linha, problema),
+            _ => panic!("Arquivo aceito: {:?}", texto),
+        };
+        assert_eq!(erro("2\n 3\n 4 1 2\n 2 3 1\n 6 1 1\n 2\n 5 2 2\n"),
+                   (7,
+                    Problema::NumerosFaltando {
+                        esperados: 6,
+                        lidos: 3,
+                    }));
+        assert_eq!(erro("1\n 1\n 4 1 2\n 9\n"), (4, Problema::LinhaExtra));
+        assert_eq!(erro("1\n 1\n -4 1 2\n"), (3, Problema::DuracaoNegativa(-4)));
+
+        match le_sch_de("sch.txt", SCH.as_bytes(), 1.5) {
+            Err(Erro::Parametro { nome, .. }) => assert_eq!(nome, "h"),
+            _ => panic!("h = 1.5 aceito"),
+        }
+    }
+
+    #[test]
+    fn seleciona_por_indice() {
+        let instancias = || le_wt_de("wt3.txt", WT.as_bytes(), 3).unwrap();
+        assert_eq!(tarefas(&seleciona(instancias(), 2).unwrap())[0], (1, 8, 0, 2));
+
+        for &indice in &[0, 3] {
+            match seleciona(instancias(), indice) {
+                Err(Erro::Parametro { nome, valor }) => {
+                    assert_eq!(nome, "indice");
+                    assert_eq!(valor, indice.to_string());
+                }
+                _ => panic!("Índice {} aceito", indice),
+            }
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use std::cmp::max;
+use std::mem;
+use erro::Erro;
+use instancia::{Instancia, IdTarefa, Solucao, Avaliador, Temporizacao, INF, verifica_instancia};
+use solver::{Solver, Resultado, Parametros, Parada};
+use objetivo::{Objetivo, AdiantamentoAtraso, Agregacao};
+
+// Programação dinâmica sobre subconjuntos de tarefas. Sem ociosidade, o
+// subconjunto S termina no instante P(S), a soma das durações, então o melhor
+// custo de S é o melhor entre terminar com cada j de S após S \ {j}. Tempo
+// O(n 2^n) e memória O(2^n).
+
+// Memória por subconjunto: o custo e a última tarefa
+const BYTES_POR_SUBCONJUNTO: u64 = (mem::size_of::<i32>() + mem::size_of::<u8>()) as u64;
+
+pub fn solve(av: &Avaliador) -> Resultado {
+    let inst = av.inst();
+    let n = inst.num_tarefas();
+    let objetivo = av.objetivo();
+    let combina = |a: i32, b: i32| match objetivo.agregacao() {
+        Agregacao::Soma => a + b,
+        Agregacao::Maximo => max(a, b),
+    };
+    let vazio = match objetivo.agregacao() {
+        Agregacao::Soma => 0,
+        Agregacao::Maximo => -INF,
+    };
+
+    let num_subconjuntos = 1usize << n;
+    let mut custo = vec![INF; num_subconjuntos];
+    let mut ultima = vec![0u8; num_subconjuntos];
+    custo[0] = vazio;
+
+    for s in 1..num_subconjuntos {
+        let tarefas = (0..n).filter(|&j| s & (1 << j) != 0);
+        let fim: i32 = tarefas.clone().map(|j| inst.tarefa(j).duracao()).sum();
+        for j in tarefas {
+            av.conta_avaliacao();
+            let c = combina(custo[s ^ (1 << j)], objetivo.custo(inst.tarefa(j), fim));
+            if c < custo[s] {
+                custo[s] = c;
+                ultima[s] = j as u8;
+            }
+        }
+    }
+
+    // Reconstrói a sequência do fim para o início
+    let mut sequencia = Vec::with_capacity(n);
+    let mut s = num_subconjuntos - 1;
+    while s != 0 {
+        let j = ultima[s] as IdTarefa;
+        sequencia.push(j);
+        s ^= 1 << j;
+    }
+    sequencia.reverse();
+
+    Resultado {
+        solucao: Solucao::com_fo(sequencia, custo[num_subconjuntos - 1]),
+        it_alvo: 0,
+        num_iter: num_subconjuntos as u64,
+        estatisticas: av.estatisticas(),
+        parada: Parada::Otimo,
+        semente: 0,
+    }
+}
+
+pub struct ProgramacaoDinamica<'a> {
+    inst: &'a Instancia,
+    // Memória máxima das tabelas, em MiB
+    memoria_max: u64,
+    objetivo: &'a dyn Objetivo,
+    temporizacao: Temporizacao,
+}
+
+impl<'a> ProgramacaoDinamica<'a> {
+    pub fn new(inst: &'a Instancia) -> ProgramacaoDinamica<'a> {
+        ProgramacaoDinamica {
+            inst,
+            // Até 25 tarefas
+            memoria_max: 256,
+            objetivo: &AdiantamentoAtraso,
+            temporizacao: Temporizacao::Semiativa,
+        }
+    }
+
+    pub fn memoria_max(&mut self, mib: u64) -> &mut ProgramacaoDinamica<'a> {
+        self.memoria_max = mib;
+        self
+    }
+
+    pub fn objetivo(&mut self, objetivo: &'a dyn Objetivo) -> &mut ProgramacaoDinamica<'a> {
+        self.objetivo = objetivo;
+        self
+    }
+
+    // A temporização ótima só é aceita com objetivos regulares, em que ela
+    // coincide com a semiativa
+    pub fn temporizacao(&mut self, temporizacao: Temporizacao) -> &mut ProgramacaoDinamica<'a> {
+        self.temporizacao = temporizacao;
+        self
+    }
+
+    fn verifica(&self) -> Result<(), Erro> {
+        verifica_instancia(self.inst)?;
+
+        let nao_suporta = |motivo: String| {
+            Err(Erro::NaoSuportado {
+                algoritmo: self.nome(),
+                motivo,
+            })
+        };
+        if self.temporizacao != Temporizacao::Semiativa && !self.objetivo.regular() {
+            return nao_suporta(format!("a temporização ótima com o objetivo {}",
+                                       self.objetivo.nome()));
+        }
+        if self.inst.tem_setup() {
+            return nao_suporta("tempos de setup".to_string());
+        }
+        if (0..self.inst.num_tarefas()).any(|t| self.inst.tarefa(t).liberacao() > 0) {
+            return nao_suporta("datas de liberação".to_string());
+        }
+
+        let n = self.inst.num_tarefas() as u32;
+        let memoria = 1u64.checked_shl(n)
+            .and_then(|s| s.checked_mul(BYTES_POR_SUBCONJUNTO))
+            .map(|b| b.div_ceil(1 << 20));
+        match memoria {
+            Some(mib) if mib <= self.memoria_max => Ok(()),
+            _ => {
+                nao_suporta(format!("{} tarefas com {} MiB de memória (use até {} tarefas)",
+                                    n,
+                                    self.memoria_max,
+                                    self.max_tarefas()))
+            }
+        }
+    }
+
+    // Maior número de tarefas cujas tabelas cabem em `memoria_max`
+    fn max_tarefas(&self) -> u32 {
+        let subconjuntos = (self.memoria_max << 20) / BYTES_POR_SUBCONJUNTO;
+        63 - subconjuntos.max(1).leading_zeros()
+    }
+}
+
+impl<'a> Solver for ProgramacaoDinamica<'a> {
+    fn nome(&self) -> &'static str {
+        "ProgramacaoDinamica"
+    }
+
+    fn parametros(&self) -> Parametros {
+        vec![("memoria_max_mib", self.memoria_max.to_string())]
+    }
+
+    fn solve(&self) -> Result<Resultado, Erro> {
+        self.verifica()?;
+        Ok(solve(&Avaliador::new(self.inst, self.objetivo, self.temporizacao)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ag::{self, Cruzamento, Mutacao};
+    use bb;
+    use gerador::Gerador;
+    use grasp;
+    use instancia::{is_factivel, neh};
+    use solver::{Criterios, Incumbente, gera_rng};
+
+    // Instâncias de 1 a 8 tarefas com pesos, geradas com sementes fixas
+    fn instancias() -> Vec<Instancia> {
+        (1..9).map(|n| Gerador::new(n).peso_max(5).semente(n as u64).gera().unwrap()).collect()
+    }
+
+    // Confere que `sol` é uma permutação com a FO correta e não melhor que o
+    // ótimo
+    fn confere(av: &Avaliador, sol: &Solucao, otimo: i32) {
+        assert!(is_factivel(sol.sequencia(), av.inst().num_tarefas()));
+        assert_eq!(sol.fo(), Solucao::new(av, sol.sequencia().clone()).fo());
+        assert!(sol.fo() >= otimo);
+    }
+
+    #[test]
+    fn otimo_confere_com_a_sequencia() {
+        for inst in instancias() {
+            let av = Avaliador::new(&inst, &AdiantamentoAtraso, Temporizacao::Semiativa);
+            let res = solve(&av);
+            confere(&av, &res.solucao, res.solucao.fo());
+        }
+    }
+
+    #[test]
+    fn neh_semiguloso_e_vnd() {
+        let mut rng = gera_rng(1);
+        for inst in instancias() {
+            let av = Avaliador::new(&inst, &AdiantamentoAtraso, Temporizacao::Semiativa);
+            let otimo = solve(&av).solucao.fo();
+            for &alfa in &[0.01, 0.5, 1.0] {
+                let inicial = grasp::neh_semiguloso(&mut rng, &av, alfa).unwrap();
+                confere(&av, &inicial, otimo);
+
+                let vizinho = grasp::vnd(&mut rng, &av, &inicial, 60);
+                confere(&av, &vizinho, otimo);
+                assert!(vizinho.fo() <= inicial.fo());
+            }
+        }
+    }
+
+    // O AG é heurístico: a melhor de algumas sementes fica a até 10% do ótimo
+    #[test]
+    fn ag_perto_do_otimo() {
+        let criterios = Criterios {
+            max_iter_total: Some(100),
+            ..Criterios::default()
+        };
+        for inst in instancias() {
+            let av = Avaliador::new(&inst, &AdiantamentoAtraso, Temporizacao::Semiativa);
+            let otimo = solve(&av).solucao.fo();
+            let melhor = (0..5)
+                .map(|semente| {
+                    let res = ag::solve(&mut gera_rng(semente),
+                                        &av,
+                                        &criterios,
+                                        50,
+                                        0.95,
+                                        Cruzamento::OX,
+                                        Mutacao::Swap,
+                                        0.2,
+                                        &Incumbente::new());
+                    confere(&av, &res.solucao, otimo);
+                    res.solucao.fo()
+                })
+                .min()
+                .unwrap();
+            assert!(melhor * 10 <= otimo * 11,
+                    "{} tarefas: {} para o ótimo {}",
+                    inst.num_tarefas(),
+                    melhor,
+                    otimo);
+        }
+    }
+
+    #[test]
+    fn branch_bound_igual_pd() {
+        for inst in instancias() {
+            let av = Avaliador::new(&inst, &AdiantamentoAtraso, Temporizacao::Semiativa);
+            let otimo = solve(&av).solucao.fo();
+            let res = bb::solve(&av, &Criterios::default(), neh(&av));
+            confere(&av, &res.resultado.solucao, otimo);
+            assert!(res.otimo());
+            assert_eq!(res.resultado.solucao.fo(), otimo);
+        }
+    }
+}
@@ -0,0 +1,341 @@
+extern crate rand;
+
+use std::time::Duration;
+use self::rand::Rng;
+use instancia::{Instancia, Solucao, Avaliador, Temporizacao, verifica_instancia};
+use erro::{Erro, verifica_parametro};
+use grasp::{self, Construcao, Operador};
+use solver::{Solver, Resultado, Criterios, Parametros, Incumbente, multi_start, num_threads,
+             semente_aleatoria, sequencia_unica};
+use objetivo::{Objetivo, AdiantamentoAtraso};
+use vizinhanca::{Delta, Movimento};
+
+// Movimentos sorteados para estimar a temperatura inicial
+const NUM_AMOSTRAS: usize = 100;
+// Menor fator de redução da temperatura no resfriamento adaptativo
+const FATOR_MIN: f64 = 0.5;
+
+// Esquema de resfriamento aplicado ao fim de cada nível de temperatura
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Resfriamento {
+    // T <- alfa * T
+    Geometrico(f64),
+    // T <- T * exp(-lambda * T / sigma), em que sigma é o desvio padrão da FO
+    // da solução atual durante o nível (Huang, Romeo e Sangiovanni-Vincentelli)
+    Adaptativo(f64),
+}
+
+// Parâmetros do recozimento, além dos critérios de parada
+#[derive(Clone, Copy, Debug)]
+pub struct Esquema {
+    pub resfriamento: Resfriamento,
+    // Movimentos sorteados em cada nível de temperatura
+    pub iter_temperatura: u64,
+    // Probabilidade de aceitar uma piora média no início, usada para estimar
+    // a temperatura inicial
+    pub aceitacao_inicial: f64,
+    // Substitui a temperatura inicial estimada
+    pub temperatura_inicial: Option<f64>,
+    // Fração da temperatura inicial adotada no reaquecimento
+    pub reaquecimento: f64,
+    // Níveis sem melhoria até o reaquecimento; 0 desliga o reaquecimento
+    pub estagnacao: u64,
+}
+
+fn sorteia<R: Rng + Sized>(rng: &mut R, operadores: &[Operador<R>], n: usize) -> Movimento {
+    let operador = operadores[rng.gen::<usize>() % operadores.len()];
+    operador(rng, n)
+}
+
+// Temperatura em que uma piora média entre movimentos sorteados a partir de
+// `sol` é aceita com probabilidade `aceitacao`
+fn estima_temperatura<R: Rng + Sized>(rng: &mut R,
+                                      av: &Avaliador,
+                                      sol: &Solucao,
+                                      operadores: &[Operador<R>],
+                                      aceitacao: f64)
+                                      -> f64 {
+    let delta = Delta::new(av, sol);
+    let n = sol.sequencia().len();
+    let mut soma = 0.0;
+    let mut pioras = 0;
+    for _ in (0..NUM_AMOSTRAS).take_while(|_| !av.esgotado()) {
+        let variacao = delta.fo(sorteia(rng, operadores, n)) - sol.fo();
+        if variacao > 0 {
+            soma += variacao as f64;
+            pioras += 1;
+        }
+    }
+
+    if pioras == 0 {
+        1.0
+    } else {
+        -(soma / pioras as f64) / aceitacao.ln()
+    }
+}
+
+// Recozimento simulado. Cada iteração é um nível de temperatura com
+// `iter_temperatura` movimentos sorteados; pioras de d são aceitas com
+// probabilidade exp(-d / T). Após `estagnacao` níveis sem melhorar a melhor
+// solução, a busca volta a ela com a temperatura reaquecida.
+pub fn solve<R: Rng + Sized>(rng: &mut R,
+                             av: &Avaliador,
+                             construcao: Construcao,
+                             esquema: &Esquema,
+                             criterios: &Criterios,
+                             incumbente: &Incumbente)
+                             -> Resultado {
+    let n = av.inst().num_tarefas();
+    let operadores = grasp::operadores(n);
+
+    av.limita_avaliacoes(criterios.max_avaliacoes);
+    let mut atual = grasp::construcao(rng, av, grasp::ALFA_INICIAL, construcao);
+    let mut best = atual.clone();
+    incumbente.atualiza(&best);
+
+    let t_inicial = match esquema.temperatura_inicial {
+        Some(t) => t,
+        None if n >= 2 => estima_temperatura(rng, av, &atual, &operadores, esquema.aceitacao_inicial),
+        None => 1.0,
+    };
+    let mut t = t_inicial;
+    let mut sem_melhoria = 0;
+    let mut it = 0;
+    let mut it_alvo = 0;
+
+    let parada = loop {
+        if let Some(parada) = sequencia_unica(av)
+            .or_else(|| criterios.verifica(av, it, it_alvo, incumbente.fo())) {
+            break parada;
+        }
+
+        // Soma e soma dos quadrados da FO atual, para o desvio padrão
+        let mut soma = 0.0;
+        let mut soma_quad = 0.0;
+        let mut melhorou = false;
+        let mut k = 0;
+        while k < esquema.iter_temperatura && !av.esgotado() {
+            // A solução atual só muda quando um movimento é aceito
+            let aceita = {
+                let delta = Delta::new(av, &atual);
+                let mut aceita = None;
+                while k < esquema.iter_temperatura && aceita.is_none() && !av.esgotado() {
+                    k += 1;
+                    let mov = sorteia(rng, &operadores, n);
+                    let fo = delta.fo(mov);
+                    let piora = (fo - atual.fo()) as f64;
+                    if piora <= 0.0 || rng.gen::<f64>() < (-piora / t).exp() {
+                        aceita = Some(delta.aplica(mov, fo));
+                    } else {
+                        soma += atual.fo() as f64;
+                        soma_quad += atual.fo() as f64 * atual.fo() as f64;
+                    }
+                }
+                aceita
+            };
+
+            if let Some(vizinho) = aceita {
+                atual = vizinho;
+                soma += atual.fo() as f64;
+                soma_quad += atual.fo() as f64 * atual.fo() as f64;
+
+                if atual.fo() < best.fo() {
+                    best = atual.clone();
+                    melhorou = true;
+                    av.conta_melhoria();
+                    incumbente.atualiza(&best);
+                }
+            }
+        }
+
+        if melhorou {
+            it_alvo = it;
+            sem_melhoria = 0;
+        } else {
+            sem_melhoria += 1;
+        }
+
+        if esquema.estagnacao > 0 && sem_melhoria >= esquema.estagnacao {
+            t = esquema.reaquecimento * t_inicial;
+            atual = best.clone();
+            sem_melhoria = 0;
+        } else {
+            t *= match esquema.resfriamento {
+                Resfriamento::Geometrico(alfa) => alfa,
+                Resfriamento::Adaptativo(lambda) => {
+                    let media = soma / k as f64;
+                    let sigma = (soma_quad / k as f64 - media * media).max(0.0).sqrt();
+                    if sigma > 0.0 {
+                        (-lambda * t / sigma).exp().max(FATOR_MIN)
+                    } else {
+                        FATOR_MIN
+                    }
+                }
+            };
+        }
+
+        it += 1;
+    };
+
+    Resultado {
+        solucao: best,
+        it_alvo,
+        num_iter: it,
+        estatisticas: av.estatisticas(),
+        parada,
+        semente: 0,
+    }
+}
+
+pub struct Recozimento<'a> {
+    inst: &'a Instancia,
+    esquema: Esquema,
+    construcao: Construcao,
+    criterios: Criterios,
+    threads: usize,
+    objetivo: &'a dyn Objetivo,
+    temporizacao: Temporizacao,
+    semente: Option<u64>,
+}
+
+impl<'a> Recozimento<'a> {
+    pub fn new(inst: &'a Instancia) -> Recozimento<'a> {
+        Recozimento {
+            inst,
+            esquema: Esquema {
+                resfriamento: Resfriamento::Geometrico(0.95),
+                iter_temperatura: 1000,
+                aceitacao_inicial: 0.8,
+                temperatura_inicial: None,
+                reaquecimento: 0.5,
+                estagnacao: 20,
+            },
+            construcao: Construcao::Neh,
+            criterios: Criterios {
+                tempo: Some(Duration::from_secs(30)),
+                ..Criterios::default()
+            },
+            threads: 1,
+            objetivo: &AdiantamentoAtraso,
+            temporizacao: Temporizacao::Semiativa,
+            semente: None,
+        }
+    }
+
+    pub fn resfriamento(&mut self, resfriamento: Resfriamento) -> &mut Recozimento<'a> {
+        self.esquema.resfriamento = resfriamento;
+        self
+    }
+
+    pub fn iter_temperatura(&mut self, iter_temperatura: u64) -> &mut Recozimento<'a> {
+        self.esquema.iter_temperatura = iter_temperatura;
+        self
+    }
+
+    pub fn aceitacao_inicial(&mut self, aceitacao: f64) -> &mut Recozimento<'a> {
+        self.esquema.aceitacao_inicial = aceitacao;
+        self
+    }
+
+    // Fixa a temperatura inicial em vez de estimá-la pela aceitação inicial
+    pub fn temperatura_inicial(&mut self, temperatura: f64) -> &mut Recozimento<'a> {
+        self.esquema.temperatura_inicial = Some(temperatura);
+        self
+    }
+
+    pub fn reaquecimento(&mut self, fracao: f64) -> &mut Recozimento<'a> {
+        self.esquema.reaquecimento = fracao;
+        self
+    }
+
+    pub fn estagnacao(&mut self, niveis: u64) -> &mut Recozimento<'a> {
+        self.esquema.estagnacao = niveis;
+        self
+    }
+
+    // Construção da solução inicial
+    pub fn construcao(&mut self, construcao: Construcao) -> &mut Recozimento<'a> {
+        self.construcao = construcao;
+        self
+    }
+
+    metodos_criterios!();
+
+    pub fn objetivo(&mut self, objetivo: &'a dyn Objetivo) -> &mut Recozimento<'a> {
+        self.objetivo = objetivo;
+        self
+    }
+
+    // Número de threads da execução paralela; 0 usa todos os núcleos
+    pub fn threads(&mut self, threads: usize) -> &mut Recozimento<'a> {
+        self.threads = threads;
+        self
+    }
+
+    pub fn semente(&mut self, semente: u64) -> &mut Recozimento<'a> {
+        self.semente = Some(semente);
+        self
+    }
+
+    pub fn temporizacao(&mut self, temporizacao: Temporizacao) -> &mut Recozimento<'a> {
+        self.temporizacao = temporizacao;
+        self
+    }
+}
+
+impl<'a> Solver for Recozimento<'a> {
+    fn nome(&self) -> &'static str {
+        "Recozimento"
+    }
+
+    fn parametros(&self) -> Parametros {
+        let esquema = &self.esquema;
+        let mut parametros = vec![("resfriamento", format!("{:?}", esquema.resfriamento)),
+                                  ("iter_temperatura", esquema.iter_temperatura.to_string()),
+                                  ("aceitacao_inicial", esquema.aceitacao_inicial.to_string()),
+                                  ("reaquecimento", esquema.reaquecimento.to_string()),
+                                  ("estagnacao", esquema.estagnacao.to_string()),
+                                  ("construcao", format!("{:?}", self.construcao)),
+                                  ("threads", self.threads.to_string())];
+        if let Some(t) = esquema.temperatura_inicial {
+            parametros.push(("temperatura_inicial", t.to_string()));
+        }
+        parametros.extend(self.criterios.parametros());
+        parametros
+    }
+
+    fn solve(&self) -> Result<Resultado, Erro> {
+        verifica_instancia(self.inst)?;
+        let esquema = &self.esquema;
+        match esquema.resfriamento {
+            Resfriamento::Geometrico(alfa) => {
+                verifica_parametro("alfa", alfa, alfa > 0.0 && alfa < 1.0)?
+            }
+            Resfriamento::Adaptativo(lambda) => verifica_parametro("lambda", lambda, lambda > 0.0)?,
+        }
+        verifica_parametro("iter_temperatura",
+                           esquema.iter_temperatura,
+                           esquema.iter_temperatura > 0)?;
+        verifica_parametro("aceitacao_inicial",
+                           esquema.aceitacao_inicial,
+                           esquema.aceitacao_inicial > 0.0 && esquema.aceitacao_inicial < 1.0)?;
+        if let Some(t) = esquema.temperatura_inicial {
+            verifica_parametro("temperatura_inicial", t, t > 0.0)?;
+        }
+        verifica_parametro("reaquecimento",
+                           esquema.reaquecimento,
+                           esquema.reaquecimento > 0.0 && esquema.reaquecimento <= 1.0)?;
+
+        let threads = num_threads(self.threads);
+        let criterios = self.criterios.divide(threads as u64);
+        let semente = self.semente.unwrap_or_else(semente_aleatoria);
+        Ok(multi_start(threads, semente, |rng, incumbente| {
+            solve(rng,
+                  &Avaliador::new(self.inst, self.objetivo, self.temporizacao),
+                  self.construcao,
+                  &self.esquema,
+                  &criterios,
+                  incumbente)
+        }))
+    }
+}
@@ -0,0 +1,187 @@
+use std::cmp::max;
+use std::io::{self, Write};
+use serde::Serialize;
+use instancia::{Avaliador, IdTarefa, Sequencia};
+
+// Relatório dos tempos de cada tarefa de uma sequência, em tabela, CSV ou JSON
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FormatoRelatorio {
+    Tabela,
+    Csv,
+    Json,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct LinhaRelatorio {
+    pub id: IdTarefa,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nome: Option<String>,
+    pub inicio: i32,
+    pub fim: i32,
+    pub entrega: i32,
+    pub adiantamento: i32,
+    pub atraso: i32,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Relatorio {
+    // Tarefas na ordem da sequência
+    pub tarefas: Vec<LinhaRelatorio>,
+    pub total_adiantamento: i32,
+    pub total_atraso: i32,
+    pub adiantadas: usize,
+    pub pontuais: usize,
+    pub atrasadas: usize,
+}
+
+impl Relatorio {
+    // Tempos obtidos pela simulação da sequência com a temporização do
+    // avaliador (`Solucao::executar_simulacao` na semiativa)
+    pub fn new(av: &Avaliador, sequencia: &Sequencia) -> Relatorio {
+        let inst = av.inst();
+        let fim = av.finalizacao(sequencia);
+        let tarefas: Vec<_> = sequencia.iter()
+            .map(|&id| {
+                let t = inst.tarefa(id);
+                LinhaRelatorio {
+                    id,
+                    nome: t.nome().map(|n| n.to_string()),
+                    inicio: fim[id] - t.duracao(),
+                    fim: fim[id],
+                    entrega: t.entrega(),
+                    adiantamento: max(0, t.entrega() - fim[id]),
+                    atraso: max(0, fim[id] - t.entrega()),
+                }
+            })
+            .collect();
+
+        Relatorio {
+            total_adiantamento: tarefas.iter().map(|t| t.adiantamento).sum(),
+            total_atraso: tarefas.iter().map(|t| t.atraso).sum(),
+            adiantadas: tarefas.iter().filter(|t| t.adiantamento > 0).count(),
+            pontuais: tarefas.iter().filter(|t| t.fim == t.entrega).count(),
+            atrasadas: tarefas.iter().filter(|t| t.atraso > 0).count(),
+            tarefas,
+        }
+    }
+
+    pub fn escreve<W: Write>(&self, saida: &mut W, formato: FormatoRelatorio) -> io::Result<()> {
+        match formato {
+            FormatoRelatorio::Tabela => self.escreve_tabela(saida),
+            FormatoRelatorio::Csv => self.escreve_csv(saida),
+            FormatoRelatorio::Json => self.escreve_json(saida),
+        }
+    }
+
+    pub fn escreve_tabela<W: Write>(&self, saida: &mut W) -> io::Result<()> {
+        writeln!(saida,
+                 "{:>5} {:>6} {:>8} {:>8} {:>8} {:>12} {:>8}",
+                 "Pos",
+                 "Tarefa",
+                 "Inicio",
+                 "Fim",
+                 "Entrega",
+                 "Adiantamento",
+                 "Atraso")?;
+        for (pos, t) in self.tarefas.iter().enumerate() {
+            writeln!(saida,
+                     "{:>5} {:>6} {:>8} {:>8} {:>8} {:>12} {:>8}",
+                     pos,
+                     t.id,
+                     t.inicio,
+                     t.fim,
+                     t.entrega,
+                     t.adiantamento,
+                     t.atraso)?;
+        }
+        writeln!(saida,
+                 "{:>5} {:>6} {:>8} {:>8} {:>8} {:>12} {:>8}",
+                 "Total",
+                 "",
+                 "",
+                 "",
+                 "",
+                 self.total_adiantamento,
+                 self.total_atraso)?;
+        writeln!(saida,
+                 "Adiantadas: {}, no prazo: {}, atrasadas: {}",
+                 self.adiantadas,
+                 self.pontuais,
+                 self.atrasadas)
+    }
+
+    // As linhas das tarefas e, após uma linha em branco, o resumo com os
+    // totais e as contagens
+    pub fn escreve_csv<W: Write>(&self, saida: &mut W) -> io::Result<()> {
+        writeln!(saida, "Posicao,Tarefa,Nome,Inicio,Fim,Entrega,Adiantamento,Atraso")?;
+        for (pos, t) in self.tarefas.iter().enumerate() {
+            writeln!(saida,
+                     "{},{},{},{},{},{},{},{}",
+                     pos,
+                     t.id,
+                     t.nome.as_deref().unwrap_or(""),
+                     t.inicio,
+                     t.fim,
+                     t.entrega,
+                     t.adiantamento,
+                     t.atraso)?;
+        }
+        writeln!(saida)?;
+        writeln!(saida, "TotalAdiantamento,TotalAtraso,Adiantadas,Pontuais,Atrasadas")?;
+        writeln!(saida,
+                 "{},{},{},{},{}",
+                 self.total_adiantamento,
+                 self.total_atraso,
+                 self.adiantadas,
+                 self.pontuais,
+                 self.atrasadas)
+    }
+
+    pub fn escreve_json<W: Write>(&self, saida: &mut W) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut *saida, self)?;
+        writeln!(saida)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use instancia::{Instancia, Tarefa, Temporizacao};
+    use objetivo::AdiantamentoAtraso;
+
+    #[test]
+    fn totais_e_contagens() {
+        // Adiantada em 3, no prazo, atrasada em 2 e atrasada em 3 por esperar
+        // a liberação em 12
+        let inst = Instancia::new(vec![Tarefa::new(2, 5),
+                                       Tarefa::new(3, 5),
+                                       Tarefa::new(4, 7),
+                                       Tarefa::completa(1, 10, 1, 1, 12)]);
+        let av = Avaliador::new(&inst, &AdiantamentoAtraso, Temporizacao::Semiativa);
+        let rel = Relatorio::new(&av, &vec![0, 1, 2, 3]);
+
+        let tempos: Vec<_> = rel.tarefas
+            .iter()
+            .map(|t| (t.id, t.inicio, t.fim, t.adiantamento, t.atraso))
+            .collect();
+        assert_eq!(tempos, [(0, 0, 2, 3, 0), (1, 2, 5, 0, 0), (2, 5, 9, 0, 2), (3, 12, 13, 0, 3)]);
+        assert_eq!((rel.total_adiantamento, rel.total_atraso), (3, 5));
+        assert_eq!((rel.adiantadas, rel.pontuais, rel.atrasadas), (1, 1, 2));
+
+        let mut csv = vec![];
+        rel.escreve_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let linhas: Vec<_> = csv.lines().collect();
+        assert_eq!(linhas.len(), 8);
+        assert_eq!(linhas[4], "3,3,,12,13,10,0,3");
+        assert_eq!(linhas[7], "3,5,1,1,2");
+
+        let mut json = vec![];
+        rel.escreve_json(&mut json).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(json["total_atraso"], 5);
+        assert_eq!(json["pontuais"], 1);
+        assert_eq!(json["tarefas"].as_array().unwrap().len(), 4);
+    }
+}
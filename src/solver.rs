@@ -0,0 +1,302 @@
+extern crate rand;
+
+use std::cmp::max;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::thread;
+use std::time::Duration;
+use self::rand::{SeedableRng, XorShiftRng};
+use erro::Erro;
+use instancia::{Solucao, Estatisticas, Avaliador};
+
+// Critério que encerrou a execução
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Parada {
+    // Tempo limite atingido
+    Tempo,
+    // Número máximo de iterações sem melhoria atingido
+    SemMelhoria,
+    // Número máximo de iterações atingido
+    Iteracoes,
+    // Número máximo de avaliações da FO atingido
+    Avaliacoes,
+    // FO alvo alcançada
+    Alvo,
+    // Otimalidade provada por um método exato
+    Otimo,
+}
+
+// Parada das buscas por vizinhança em instâncias com menos de duas tarefas,
+// que têm uma única sequência possível
+pub fn sequencia_unica(av: &Avaliador) -> Option<Parada> {
+    if av.inst().num_tarefas() < 2 {
+        Some(Parada::Otimo)
+    } else {
+        None
+    }
+}
+
+// Condições de parada. Os critérios ausentes não limitam a execução, e ela
+// termina assim que qualquer um dos definidos é satisfeito. Os critérios são
+// verificados entre iterações, mas o limite de avaliações também interrompe
+// a iteração em andamento (ver `Avaliador::esgotado`): uma construção
+// interrompida ainda é completada e avaliada uma vez.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Criterios {
+    pub tempo: Option<Duration>,
+    // Iterações consecutivas sem melhoria
+    pub max_iter: Option<u64>,
+    pub max_iter_total: Option<u64>,
+    pub max_avaliacoes: Option<u64>,
+    // Para assim que a FO for menor ou igual ao alvo
+    pub alvo: Option<i32>,
+}
+
+impl Criterios {
+    // Critérios de `self`, substituídos pelos que estiverem definidos em `outro`
+    pub fn sobrepoe(&self, outro: &Criterios) -> Criterios {
+        Criterios {
+            tempo: outro.tempo.or(self.tempo),
+            max_iter: outro.max_iter.or(self.max_iter),
+            max_iter_total: outro.max_iter_total.or(self.max_iter_total),
+            max_avaliacoes: outro.max_avaliacoes.or(self.max_avaliacoes),
+            alvo: outro.alvo.or(self.alvo),
+        }
+    }
+
+    // Critérios definidos, como parâmetros
+    pub fn parametros(&self) -> Parametros {
+        let mut parametros = vec![];
+        if let Some(tempo) = self.tempo {
+            parametros.push(("tempo_ms", tempo.as_millis().to_string()));
+        }
+        if let Some(max_iter) = self.max_iter {
+            parametros.push(("max_iter", max_iter.to_string()));
+        }
+        if let Some(max_iter_total) = self.max_iter_total {
+            parametros.push(("max_iter_total", max_iter_total.to_string()));
+        }
+        if let Some(max_avaliacoes) = self.max_avaliacoes {
+            parametros.push(("max_avaliacoes", max_avaliacoes.to_string()));
+        }
+        if let Some(alvo) = self.alvo {
+            parametros.push(("alvo", alvo.to_string()));
+        }
+        parametros
+    }
+
+    // Critérios de cada uma de `partes` execuções paralelas: os orçamentos de
+    // avaliações e de iterações totais são divididos entre elas
+    pub fn divide(&self, partes: u64) -> Criterios {
+        let parte = |limite: u64| limite.div_ceil(partes);
+        Criterios {
+            max_iter_total: self.max_iter_total.map(parte),
+            max_avaliacoes: self.max_avaliacoes.map(parte),
+            ..*self
+        }
+    }
+
+    // Critério satisfeito após `it` iterações, a última melhoria em `it_alvo`
+    // e a melhor FO `fo`, se houver algum
+    pub fn verifica(&self, av: &Avaliador, it: u64, it_alvo: u64, fo: i32) -> Option<Parada> {
+        let estatisticas = av.estatisticas();
+        let excede = |limite: Option<u64>, valor: u64| limite.is_some_and(|l| valor >= l);
+
+        if self.alvo.is_some_and(|alvo| fo <= alvo) {
+            Some(Parada::Alvo)
+        } else if excede(self.max_avaliacoes, estatisticas.avaliacoes) {
+            Some(Parada::Avaliacoes)
+        } else if excede(self.max_iter_total, it) {
+            Some(Parada::Iteracoes)
+        } else if excede(self.max_iter, it - it_alvo) {
+            Some(Parada::SemMelhoria)
+        } else if self.tempo.is_some_and(|tempo| estatisticas.tempo >= tempo) {
+            Some(Parada::Tempo)
+        } else {
+            None
+        }
+    }
+}
+
+// Métodos dos builders que definem os critérios de parada, para structs com um
+// campo `criterios: Criterios`. Com `tempo`, só o tempo limite e `criterios`.
+macro_rules! metodos_criterios {
+    (tempo) => {
+        pub fn timeout(&mut self, timeout: u64) -> &mut Self {
+            self.criterios.tempo = Some(::std::time::Duration::from_secs(timeout));
+            self
+        }
+
+        pub fn timeout_ms(&mut self, timeout: u64) -> &mut Self {
+            self.criterios.tempo = Some(::std::time::Duration::from_millis(timeout));
+            self
+        }
+
+        // Substitui os critérios de parada definidos em `criterios`
+        pub fn criterios(&mut self, criterios: &$crate::solver::Criterios) -> &mut Self {
+            self.criterios = self.criterios.sobrepoe(criterios);
+            self
+        }
+    };
+    () => {
+        metodos_criterios!(tempo);
+
+        pub fn max_iter(&mut self, max_iter: u64) -> &mut Self {
+            self.criterios.max_iter = Some(max_iter);
+            self
+        }
+
+        pub fn max_iter_total(&mut self, max_iter_total: u64) -> &mut Self {
+            self.criterios.max_iter_total = Some(max_iter_total);
+            self
+        }
+
+        pub fn max_avaliacoes(&mut self, max_avaliacoes: u64) -> &mut Self {
+            self.criterios.max_avaliacoes = Some(max_avaliacoes);
+            self
+        }
+
+        pub fn alvo(&mut self, alvo: i32) -> &mut Self {
+            self.criterios.alvo = Some(alvo);
+            self
+        }
+    };
+}
+
+pub struct Resultado {
+    pub solucao: Solucao,
+    // Iteração em que a melhor solução foi encontrada
+    pub it_alvo: u64,
+    pub num_iter: u64,
+    pub estatisticas: Estatisticas,
+    pub parada: Parada,
+    // Semente usada pelo gerador aleatório, para reproduzir a execução
+    pub semente: u64,
+}
+
+// Parâmetros de uma execução, como pares (nome, valor)
+pub type Parametros = Vec<(&'static str, String)>;
+
+pub trait Solver {
+    fn nome(&self) -> &'static str;
+    fn solve(&self) -> Result<Resultado, Erro>;
+
+    // Parâmetros usados por `solve`, para registro junto com a solução
+    fn parametros(&self) -> Parametros {
+        vec![]
+    }
+}
+
+pub fn semente_aleatoria() -> u64 {
+    rand::random()
+}
+
+// Gerador determinístico a partir da semente. Os 64 bits da semente são
+// espalhados pelos 128 bits de estado com splitmix64, que nunca são todos
+// nulos.
+pub fn gera_rng(semente: u64) -> XorShiftRng {
+    let mut x = semente;
+    let mut proximo = || {
+        x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = x;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    };
+    let a = proximo();
+    let b = proximo();
+    XorShiftRng::from_seed([a as u32, (a >> 32) as u32, b as u32, (b >> 32) as u32 | 1])
+}
+
+// Melhor solução encontrada até o momento, compartilhada entre as threads de
+// uma execução paralela
+pub struct Incumbente {
+    fo: AtomicI32,
+    melhor: Mutex<Solucao>,
+}
+
+impl Incumbente {
+    pub fn new() -> Incumbente {
+        let melhor = Solucao::vazia();
+        Incumbente {
+            fo: AtomicI32::new(melhor.fo()),
+            melhor: Mutex::new(melhor),
+        }
+    }
+
+    pub fn fo(&self) -> i32 {
+        self.fo.load(Ordering::Relaxed)
+    }
+
+    // Substitui a incumbente se `solucao` for melhor
+    pub fn atualiza(&self, solucao: &Solucao) {
+        if solucao.fo() >= self.fo() {
+            return;
+        }
+
+        let mut melhor = self.melhor.lock().unwrap();
+        if solucao.fo() < melhor.fo() {
+            *melhor = solucao.clone();
+            self.fo.store(solucao.fo(), Ordering::Relaxed);
+        }
+    }
+}
+
+impl Default for Incumbente {
+    fn default() -> Incumbente {
+        Incumbente::new()
+    }
+}
+
+// Número de threads efetivo: 0 usa todos os núcleos disponíveis
+pub fn num_threads(threads: usize) -> usize {
+    if threads > 0 {
+        threads
+    } else {
+        thread::available_parallelism().map_or(1, |n| n.get())
+    }
+}
+
+// Executa `trabalho` em `threads` threads independentes, que compartilham a
+// mesma incumbente. Cada thread recebe um gerador derivado da semente, e a
+// thread 0 usa a própria semente, então uma execução com uma thread é igual à
+// execução sequencial. O resultado é o da thread que encontrou a melhor
+// solução, com as estatísticas somadas.
+pub fn multi_start<F>(threads: usize, semente: u64, trabalho: F) -> Resultado
+    where F: Fn(&mut XorShiftRng, &Incumbente) -> Resultado + Sync
+{
+    let incumbente = Incumbente::new();
+    let semente_thread = |i: usize| semente.wrapping_add((i as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+
+    let resultados: Vec<Resultado> = if threads <= 1 {
+        vec![trabalho(&mut gera_rng(semente), &incumbente)]
+    } else {
+        thread::scope(|escopo| {
+            let handles: Vec<_> = (0..threads)
+                .map(|i| {
+                    let trabalho = &trabalho;
+                    let incumbente = &incumbente;
+                    escopo.spawn(move || trabalho(&mut gera_rng(semente_thread(i)), incumbente))
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().expect("Erro na thread de execução")).collect()
+        })
+    };
+
+    let num_iter = resultados.iter().map(|r| r.num_iter).sum();
+    let avaliacoes = resultados.iter().map(|r| r.estatisticas.avaliacoes).sum();
+    let melhorias = resultados.iter().map(|r| r.estatisticas.melhorias).sum();
+    let tempo = resultados.iter().map(|r| r.estatisticas.tempo).fold(Duration::from_secs(0), max);
+
+    let melhor = resultados.into_iter().min_by_key(|r| r.solucao.fo()).unwrap();
+    Resultado {
+        num_iter,
+        estatisticas: Estatisticas {
+            avaliacoes,
+            tempo,
+            melhorias,
+        },
+        semente,
+        ..melhor
+    }
+}
@@ -0,0 +1,236 @@
+extern crate rand;
+
+use std::time::Duration;
+use self::rand::Rng;
+use instancia::{Instancia, Avaliador, Temporizacao, verifica_instancia};
+use erro::{Erro, verifica_parametro};
+use grasp::{self, Construcao};
+use solver::{Solver, Resultado, Criterios, Parametros, Parada, Incumbente, multi_start,
+             num_threads, semente_aleatoria, sequencia_unica};
+use objetivo::{Objetivo, AdiantamentoAtraso};
+use vizinhanca::{Delta, Movimento, Vizinhanca, movimentos};
+
+// Busca tabu sobre as vizinhanças completas. O atributo tabu é o par
+// (tarefa, posição): a tarefa deslocada não pode voltar à posição de origem
+// por `tenure` iterações, a não ser que o movimento melhore a melhor solução
+// (aspiração). Movimentos que não melhoram a solução atual são penalizados
+// pela frequência com que as tarefas já ocuparam as posições de destino.
+#[allow(clippy::too_many_arguments)]
+pub fn solve<R: Rng + Sized>(rng: &mut R,
+                             av: &Avaliador,
+                             construcao: Construcao,
+                             vizinhancas: &[Vizinhanca],
+                             tenure: u64,
+                             diversificacao: f64,
+                             criterios: &Criterios,
+                             incumbente: &Incumbente)
+                             -> Resultado {
+    let n = av.inst().num_tarefas();
+    let movs: Vec<Movimento> = vizinhancas.iter().flat_map(|&v| movimentos(v, n)).collect();
+
+    av.limita_avaliacoes(criterios.max_avaliacoes);
+    let mut atual = grasp::construcao(rng, av, grasp::ALFA_INICIAL, construcao);
+    let mut best = atual.clone();
+    incumbente.atualiza(&best);
+
+    // tabu[t * n + p]: iteração até a qual a tarefa t não pode ir para p
+    let mut tabu = vec![0; n * n];
+    // freq[t * n + p]: iterações em que a tarefa t ocupou a posição p
+    let mut freq = vec![0u64; n * n];
+    let mut it = 0;
+    let mut it_alvo = 0;
+
+    let parada = loop {
+        if let Some(parada) = sequencia_unica(av)
+            .or_else(|| criterios.verifica(av, it, it_alvo, incumbente.fo())) {
+            break parada;
+        }
+        if movs.is_empty() {
+            // Nenhum movimento possível, como só swaps de pares adjacentes
+            // com três tarefas
+            break Parada::SemMelhoria;
+        }
+
+        for (p, &t) in atual.sequencia().iter().enumerate() {
+            freq[t * n + p] += 1;
+        }
+
+        let proxima = {
+            let delta = Delta::new(av, &atual);
+            let seq = atual.sequencia();
+            let avaliados: Vec<_> = movs.iter()
+                .take_while(|_| !av.esgotado())
+                .map(|&mov| (mov, delta.fo(mov)))
+                .collect();
+            // Maior variação da FO na vizinhança, escala da penalidade
+            let variacao = avaliados.iter().map(|&(_, fo)| (fo - atual.fo()).abs()).max().unwrap_or(0);
+
+            let mut escolhido: Option<(Movimento, i32, f64)> = None;
+            // Melhor movimento tabu, usado se todos forem tabu
+            let mut melhor_tabu: Option<(Movimento, i32, f64)> = None;
+            for &(mov, fo) in &avaliados {
+                let deslocamentos = mov.deslocamentos();
+                let proibido = deslocamentos.iter().any(|&(o, d)| tabu[seq[o] * n + d] > it);
+                let valor = if fo < atual.fo() {
+                    fo as f64
+                } else {
+                    let f: u64 = deslocamentos.iter().map(|&(o, d)| freq[seq[o] * n + d]).sum();
+                    fo as f64 + diversificacao * variacao as f64 * f as f64 / (it + 1) as f64
+                };
+
+                if proibido && fo >= best.fo() {
+                    if melhor_tabu.is_none_or(|(_, f, _)| fo < f) {
+                        melhor_tabu = Some((mov, fo, valor));
+                    }
+                } else if escolhido.is_none_or(|(_, _, v)| valor < v) {
+                    escolhido = Some((mov, fo, valor));
+                }
+            }
+
+            let (mov, fo, _) = escolhido.or(melhor_tabu).expect("Vizinhança vazia");
+            for (o, _) in mov.deslocamentos() {
+                tabu[seq[o] * n + o] = it + 1 + tenure;
+            }
+            delta.aplica(mov, fo)
+        };
+        atual = proxima;
+
+        if atual.fo() < best.fo() {
+            best = atual.clone();
+            it_alvo = it;
+            av.conta_melhoria();
+            incumbente.atualiza(&best);
+        }
+
+        it += 1;
+    };
+
+    Resultado {
+        solucao: best,
+        it_alvo,
+        num_iter: it,
+        estatisticas: av.estatisticas(),
+        parada,
+        semente: 0,
+    }
+}
+
+pub struct Tabu<'a> {
+    inst: &'a Instancia,
+    tenure: u64,
+    // Peso da penalidade por frequência; 0 desliga a diversificação
+    diversificacao: f64,
+    vizinhancas: Vec<Vizinhanca>,
+    construcao: Construcao,
+    criterios: Criterios,
+    threads: usize,
+    objetivo: &'a dyn Objetivo,
+    temporizacao: Temporizacao,
+    semente: Option<u64>,
+}
+
+impl<'a> Tabu<'a> {
+    pub fn new(inst: &'a Instancia) -> Tabu<'a> {
+        Tabu {
+            inst,
+            tenure: 10,
+            diversificacao: 0.1,
+            vizinhancas: vec![Vizinhanca::Insercao, Vizinhanca::Swap, Vizinhanca::SwapAdj],
+            construcao: Construcao::Neh,
+            criterios: Criterios {
+                tempo: Some(Duration::from_secs(30)),
+                ..Criterios::default()
+            },
+            threads: 1,
+            objetivo: &AdiantamentoAtraso,
+            temporizacao: Temporizacao::Semiativa,
+            semente: None,
+        }
+    }
+
+    // Iterações em que o retorno de uma tarefa à posição de origem é proibido
+    pub fn tenure(&mut self, tenure: u64) -> &mut Tabu<'a> {
+        self.tenure = tenure;
+        self
+    }
+
+    pub fn diversificacao(&mut self, diversificacao: f64) -> &mut Tabu<'a> {
+        self.diversificacao = diversificacao;
+        self
+    }
+
+    // Vizinhanças exploradas a cada iteração, todas por completo
+    pub fn vizinhancas(&mut self, vizinhancas: &[Vizinhanca]) -> &mut Tabu<'a> {
+        self.vizinhancas = vizinhancas.to_vec();
+        self
+    }
+
+    // Construção da solução inicial
+    pub fn construcao(&mut self, construcao: Construcao) -> &mut Tabu<'a> {
+        self.construcao = construcao;
+        self
+    }
+
+    metodos_criterios!();
+
+    pub fn objetivo(&mut self, objetivo: &'a dyn Objetivo) -> &mut Tabu<'a> {
+        self.objetivo = objetivo;
+        self
+    }
+
+    // Número de threads da execução paralela; 0 usa todos os núcleos
+    pub fn threads(&mut self, threads: usize) -> &mut Tabu<'a> {
+        self.threads = threads;
+        self
+    }
+
+    pub fn semente(&mut self, semente: u64) -> &mut Tabu<'a> {
+        self.semente = Some(semente);
+        self
+    }
+
+    pub fn temporizacao(&mut self, temporizacao: Temporizacao) -> &mut Tabu<'a> {
+        self.temporizacao = temporizacao;
+        self
+    }
+}
+
+impl<'a> Solver for Tabu<'a> {
+    fn nome(&self) -> &'static str {
+        "Tabu"
+    }
+
+    fn parametros(&self) -> Parametros {
+        let mut parametros = vec![("tenure", self.tenure.to_string()),
+                                  ("diversificacao", self.diversificacao.to_string()),
+                                  ("vizinhancas", format!("{:?}", self.vizinhancas)),
+                                  ("construcao", format!("{:?}", self.construcao)),
+                                  ("threads", self.threads.to_string())];
+        parametros.extend(self.criterios.parametros());
+        parametros
+    }
+
+    fn solve(&self) -> Result<Resultado, Erro> {
+        verifica_instancia(self.inst)?;
+        verifica_parametro("diversificacao",
+                           self.diversificacao,
+                           self.diversificacao >= 0.0)?;
+        verifica_parametro("vizinhancas",
+                           format!("{:?}", self.vizinhancas),
+                           !self.vizinhancas.is_empty())?;
+
+        let threads = num_threads(self.threads);
+        let criterios = self.criterios.divide(threads as u64);
+        let semente = self.semente.unwrap_or_else(semente_aleatoria);
+        Ok(multi_start(threads, semente, |rng, incumbente| {
+            solve(rng,
+                  &Avaliador::new(self.inst, self.objetivo, self.temporizacao),
+                  self.construcao,
+                  &self.vizinhancas,
+                  self.tenure,
+                  self.diversificacao,
+                  &criterios,
+                  incumbente)
+        }))
+    }
+}
@@ -0,0 +1,199 @@
+use serde::Deserialize;
+use documento;
+use erro::{Erro, Problema};
+use instancia::{self, Avaliador, IdTarefa, Sequencia, Solucao, abre, le_linhas, le_numeros};
+use objetivo;
+
+// Custo de uma tarefa na sequência verificada
+#[derive(Clone, Debug)]
+pub struct CustoTarefa {
+    pub id: IdTarefa,
+    pub inicio: i32,
+    pub fim: i32,
+    pub entrega: i32,
+    pub custo: i32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Verificacao {
+    // Ids que aparecem mais de uma vez, que não aparecem e que não existem
+    pub repetidas: Vec<IdTarefa>,
+    pub faltando: Vec<IdTarefa>,
+    pub invalidas: Vec<IdTarefa>,
+    // Custos na ordem da sequência e FO recalculada; vazios se a sequência
+    // não for uma permutação
+    pub tarefas: Vec<CustoTarefa>,
+    pub fo: Option<i32>,
+}
+
+impl Verificacao {
+    pub fn factivel(&self) -> bool {
+        self.fo.is_some()
+    }
+
+    // Se a sequência é factível e tem a FO informada
+    pub fn confere(&self, fo: i32) -> bool {
+        self.fo == Some(fo)
+    }
+}
+
+// Verifica se `sequencia` é uma permutação das tarefas e recalcula a FO a
+// partir da simulação da sequência (com a temporização do avaliador)
+pub fn verifica(av: &Avaliador, sequencia: &Sequencia) -> Verificacao {
+    let inst = av.inst();
+    let n = inst.num_tarefas();
+    let freq = instancia::frequencias(sequencia, n);
+    let mut invalidas: Vec<_> = sequencia.iter().cloned().filter(|&t| t >= n).collect();
+    invalidas.sort();
+    invalidas.dedup();
+
+    let mut verificacao = Verificacao {
+        repetidas: (0..n).filter(|&t| freq[t] > 1).collect(),
+        faltando: (0..n).filter(|&t| freq[t] == 0).collect(),
+        invalidas,
+        tarefas: vec![],
+        fo: None,
+    };
+    if !instancia::is_factivel(sequencia, n) {
+        return verificacao;
+    }
+
+    let fim = av.finalizacao(sequencia);
+    verificacao.tarefas = sequencia.iter()
+        .map(|&id| {
+            let t = inst.tarefa(id);
+            CustoTarefa {
+                id,
+                inicio: fim[id] - t.duracao(),
+                fim: fim[id],
+                entrega: t.entrega(),
+                custo: av.objetivo().custo(t, fim[id]),
+            }
+        })
+        .collect();
+    verificacao.fo = Some(objetivo::agrega(av.objetivo(),
+                                           sequencia.iter().map(|&id| (inst.tarefa(id), fim[id]))));
+    verificacao
+}
+
+// Campos usados de um documento de solução; os demais são ignorados
+#[derive(Deserialize)]
+struct SolucaoInformada {
+    sequencia: Sequencia,
+    fo: i32,
+}
+
+// Lê a solução a verificar. Arquivos .json e .toml seguem o formato de
+// `documento::SolucaoDoc`, e só `sequencia` e `fo` são obrigatórios. Nos
+// demais, o primeiro número é a FO e os seguintes são os ids da sequência.
+pub fn le_solucao(arquivo: &str) -> Result<Solucao, Erro> {
+    if documento::estruturado(arquivo) {
+        let sol: SolucaoInformada = documento::le(arquivo)?;
+        return Ok(Solucao::com_fo(sol.sequencia, sol.fo));
+    }
+
+    let mut numeros = vec![];
+    for (linha, texto) in le_linhas(arquivo, abre(arquivo)?)? {
+        let valores = le_numeros(&texto).map_err(|p| Erro::formato(arquivo, linha, p))?;
+        if let Some(&id) = valores.iter().skip(if numeros.is_empty() { 1 } else { 0 }).find(|&&v| v < 0) {
+            return Err(Erro::formato(arquivo, linha, Problema::Campo(id.to_string())));
+        }
+        numeros.extend(valores);
+    }
+
+    match numeros.split_first() {
+        Some((&fo, ids)) => Ok(Solucao::com_fo(ids.iter().map(|&id| id as IdTarefa).collect(), fo)),
+        None => {
+            Err(Erro::formato(arquivo,
+                              1,
+                              Problema::NumerosFaltando {
+                                  esperados: 1,
+                                  lidos: 0,
+                              }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::process;
+    use super::*;
+    use instancia::{Instancia, Tarefa, Temporizacao};
+    use objetivo::AdiantamentoAtraso;
+
+    // A tarefa 1 só é liberada em 6, e o setup de 0 para 2 é 3
+    fn instancia() -> Instancia {
+        let tarefas = vec![Tarefa::completa(2, 4, 1, 1, 0),
+                           Tarefa::completa(3, 8, 2, 1, 6),
+                           Tarefa::completa(1, 5, 1, 2, 0)];
+        let setup = vec![vec![0, 0, 3], vec![0, 0, 0], vec![1, 0, 0]];
+        Instancia::com_setup(tarefas, setup).unwrap()
+    }
+
+    fn tempos(v: &Verificacao) -> Vec<(IdTarefa, i32, i32, i32)> {
+        v.tarefas.iter().map(|t| (t.id, t.inicio, t.fim, t.custo)).collect()
+    }
+
+    #[test]
+    fn sequencia_factivel() {
+        let inst = instancia();
+        let av = Avaliador::new(&inst, &AdiantamentoAtraso, Temporizacao::Semiativa);
+
+        // A tarefa 1 espera a liberação em 6 em vez de começar em 2
+        let v = verifica(&av, &vec![0, 1, 2]);
+        assert!(v.factivel());
+        assert_eq!(tempos(&v), [(0, 0, 2, 2), (1, 6, 9, 1), (2, 9, 10, 10)]);
+        assert_eq!(v.fo, Some(13));
+        assert!(v.confere(13));
+
+        // A tarefa 2 começa depois do fim da 0 mais o setup de 3
+        let v = verifica(&av, &vec![0, 2, 1]);
+        assert_eq!(tempos(&v), [(0, 0, 2, 2), (2, 5, 6, 2), (1, 6, 9, 1)]);
+        assert!(v.confere(5));
+
+        // FO informada diferente da recalculada
+        assert!(!v.confere(4));
+        assert!(!v.confere(13));
+    }
+
+    #[test]
+    fn sequencia_infactivel() {
+        let inst = instancia();
+        let av = Avaliador::new(&inst, &AdiantamentoAtraso, Temporizacao::Semiativa);
+
+        let v = verifica(&av, &vec![0, 0, 7, 5, 7]);
+        assert!(!v.factivel());
+        assert_eq!(v.repetidas, [0]);
+        assert_eq!(v.faltando, [1, 2]);
+        assert_eq!(v.invalidas, [5, 7]);
+        assert!(v.tarefas.is_empty());
+        assert!(!v.confere(0));
+
+        let v = verifica(&av, &vec![2, 1]);
+        assert_eq!(v.faltando, [0]);
+        assert!(!v.factivel());
+    }
+
+    #[test]
+    fn le_solucao_em_texto() {
+        let arquivo = env::temp_dir().join(format!("escalonamento-{}-solucao.txt", process::id()));
+        let arquivo = arquivo.to_str().unwrap();
+
+        fs::write(arquivo, "13\n0 1\n\n2\n").unwrap();
+        let sol = le_solucao(arquivo).unwrap();
+        assert_eq!((sol.fo(), sol.sequencia().clone()), (13, vec![0, 1, 2]));
+
+        // A FO pode ser negativa, mas os ids não
+        fs::write(arquivo, "-1 0 -2 1\n").unwrap();
+        let erro = le_solucao(arquivo);
+        fs::remove_file(arquivo).unwrap();
+        match erro {
+            Err(Erro::Formato { linha, problema, .. }) => {
+                assert_eq!((linha, problema), (1, Problema::Campo("-2".to_string())));
+            }
+            _ => panic!("Id negativo aceito"),
+        }
+    }
+}
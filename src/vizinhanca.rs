@@ -0,0 +1,342 @@
+use std::cmp::{min, max};
+use instancia::{IdTarefa, Sequencia, Solucao, Avaliador};
+use objetivo::Agregacao;
+
+// Movimentos de vizinhança sobre as posições de uma sequência.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Movimento {
+    // Remove a tarefa da posição i e a insere na posição j
+    Insercao(usize, usize),
+    // Troca as tarefas das posições i e j
+    Swap(usize, usize),
+    // Troca os pares (i, i + 1) e (j, j + 1)
+    SwapAdj(usize, usize),
+    // Troca x com z e depois y com z
+    SwapXyz(usize, usize, usize),
+    // Inverte o trecho [i, k)
+    TwoOpt(usize, usize),
+}
+
+impl Movimento {
+    pub fn aplica(&self, seq: &mut Sequencia) {
+        match *self {
+            Movimento::Insercao(i, j) => {
+                let t = seq.remove(i);
+                seq.insert(j, t);
+            }
+            Movimento::Swap(i, j) => seq.swap(i, j),
+            Movimento::SwapAdj(i, j) => {
+                seq.swap(i, j);
+                seq.swap(i + 1, j + 1);
+            }
+            Movimento::SwapXyz(x, y, z) => {
+                seq.swap(x, z);
+                seq.swap(y, z);
+            }
+            Movimento::TwoOpt(i, k) => seq[i..k].reverse(),
+        }
+    }
+
+    // Primeira e última posições (inclusive) alteradas pelo movimento
+    fn intervalo(&self) -> (usize, usize) {
+        match *self {
+            Movimento::Insercao(i, j) |
+            Movimento::Swap(i, j) => (min(i, j), max(i, j)),
+            Movimento::SwapAdj(i, j) => (min(i, j), max(i, j) + 1),
+            Movimento::SwapXyz(x, y, z) => (min(x, min(y, z)), max(x, max(y, z))),
+            Movimento::TwoOpt(i, k) => (i, max(i, k.saturating_sub(1))),
+        }
+    }
+
+    // Pares (origem, destino) das posições das tarefas deslocadas pelo
+    // movimento. Na inserção, só conta a tarefa inserida, já que as demais
+    // apenas andam uma posição, a não ser entre posições vizinhas, em que a
+    // inserção é uma troca.
+    pub fn deslocamentos(&self) -> Vec<(usize, usize)> {
+        if let Movimento::Insercao(i, j) = *self {
+            if i + 1 != j && j + 1 != i {
+                return vec![(i, j)];
+            }
+        }
+        let (a, b) = self.intervalo();
+        (a..b + 1).map(|p| (self.origem(p), p)).filter(|&(o, p)| o != p).collect()
+    }
+
+    // Posição na sequência original da tarefa que ocupa `pos` após o movimento
+    fn origem(&self, pos: usize) -> usize {
+        let troca = |p: usize, a: usize, b: usize| if p == a {
+            b
+        } else if p == b {
+            a
+        } else {
+            p
+        };
+
+        match *self {
+            Movimento::Insercao(i, j) => {
+                if pos == j {
+                    i
+                } else if i < j && pos >= i && pos < j {
+                    pos + 1
+                } else if j < i && pos > j && pos <= i {
+                    pos - 1
+                } else {
+                    pos
+                }
+            }
+            Movimento::Swap(i, j) => troca(pos, i, j),
+            Movimento::SwapAdj(i, j) => troca(troca(pos, i + 1, j + 1), i, j),
+            Movimento::SwapXyz(x, y, z) => troca(troca(pos, y, z), x, z),
+            Movimento::TwoOpt(i, k) => {
+                if pos >= i && pos < k {
+                    i + k - 1 - pos
+                } else {
+                    pos
+                }
+            }
+        }
+    }
+}
+
+// Vizinhanças exploradas por completo pela busca tabu
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Vizinhanca {
+    Insercao,
+    Swap,
+    SwapAdj,
+}
+
+// Todos os movimentos da vizinhança numa sequência de `n` tarefas, em ordem
+// fixa e sem movimentos que gerem a mesma sequência duas vezes
+pub fn movimentos(vizinhanca: Vizinhanca, n: usize) -> Vec<Movimento> {
+    let mut movs = vec![];
+    match vizinhanca {
+        Vizinhanca::Insercao => {
+            for i in 0..n {
+                // Inserir i em i - 1 equivale a inserir i - 1 em i
+                for j in (0..n).filter(|&j| j != i && j + 1 != i) {
+                    movs.push(Movimento::Insercao(i, j));
+                }
+            }
+        }
+        Vizinhanca::Swap => {
+            for i in 0..n {
+                for j in i + 1..n {
+                    movs.push(Movimento::Swap(i, j));
+                }
+            }
+        }
+        Vizinhanca::SwapAdj => {
+            for i in 0..n.saturating_sub(1) {
+                for j in i + 2..n - 1 {
+                    movs.push(Movimento::SwapAdj(i, j));
+                }
+            }
+        }
+    }
+    movs
+}
+
+// Avalia movimentos a partir dos tempos de finalização da solução atual, sem
+// construir a sequência vizinha. Só as posições a partir do início do
+// movimento são simuladas, e a simulação para assim que a máquina volta ao
+// mesmo estado da solução atual.
+pub struct Delta<'a, 'b> {
+    av: &'b Avaliador<'a>,
+    solucao: &'b Solucao,
+    // Tempo de finalização de cada posição
+    fim: Vec<i32>,
+    // Custo agregado das posições [0, p) e [p, n)
+    prefixo: Vec<i32>,
+    sufixo: Vec<i32>,
+}
+
+fn combina(agregacao: Agregacao, a: i32, b: i32) -> i32 {
+    match agregacao {
+        Agregacao::Soma => a + b,
+        Agregacao::Maximo => max(a, b),
+    }
+}
+
+fn neutro(agregacao: Agregacao) -> i32 {
+    match agregacao {
+        Agregacao::Soma => 0,
+        Agregacao::Maximo => i32::MIN,
+    }
+}
+
+impl<'a, 'b> Delta<'a, 'b> {
+    pub fn new(av: &'b Avaliador<'a>, solucao: &'b Solucao) -> Delta<'a, 'b> {
+        let seq = solucao.sequencia();
+        let n = seq.len();
+        let mut delta = Delta {
+            av,
+            solucao,
+            fim: vec![],
+            prefixo: vec![],
+            sufixo: vec![],
+        };
+        if !av.incremental() {
+            return delta;
+        }
+
+        let inst = av.inst();
+        let objetivo = av.objetivo();
+        let agregacao = objetivo.agregacao();
+        let finalizacao = av.finalizacao(seq);
+        delta.fim = seq.iter().map(|&id| finalizacao[id]).collect();
+
+        let custos: Vec<_> = seq.iter()
+            .zip(&delta.fim)
+            .map(|(&id, &fim)| objetivo.custo(inst.tarefa(id), fim))
+            .collect();
+
+        delta.prefixo = vec![neutro(agregacao); n + 1];
+        delta.sufixo = vec![neutro(agregacao); n + 1];
+        for (p, &custo) in custos.iter().enumerate() {
+            delta.prefixo[p + 1] = combina(agregacao, delta.prefixo[p], custo);
+        }
+        for (p, &custo) in custos.iter().enumerate().rev() {
+            delta.sufixo[p] = combina(agregacao, delta.sufixo[p + 1], custo);
+        }
+
+        delta
+    }
+
+    // FO da solução obtida aplicando `mov` à solução atual
+    pub fn fo(&self, mov: Movimento) -> i32 {
+        if !self.av.incremental() {
+            return Solucao::new(self.av, self.vizinho(mov)).fo();
+        }
+
+        self.av.conta_avaliacao();
+
+        let seq = self.solucao.sequencia();
+        let n = seq.len();
+        let inst = self.av.inst();
+        let objetivo = self.av.objetivo();
+        let agregacao = objetivo.agregacao();
+        let (a, b) = mov.intervalo();
+
+        let mut t_atual = if a == 0 { 0 } else { self.fim[a - 1] };
+        let mut anterior: Option<IdTarefa> = if a == 0 { None } else { Some(seq[a - 1]) };
+        let mut total = self.prefixo[a];
+
+        for p in a..n {
+            if p > b && t_atual == self.fim[p - 1] && anterior == Some(seq[p - 1]) {
+                return combina(agregacao, total, self.sufixo[p]);
+            }
+
+            let id = if p <= b { seq[mov.origem(p)] } else { seq[p] };
+            let t = inst.tarefa(id);
+            t_atual = max(t_atual + inst.setup(anterior, id), t.liberacao()) + t.duracao();
+            total = combina(agregacao, total, objetivo.custo(t, t_atual));
+            anterior = Some(id);
+        }
+
+        total
+    }
+
+    pub fn vizinho(&self, mov: Movimento) -> Sequencia {
+        let mut seq = self.solucao.sequencia().clone();
+        mov.aplica(&mut seq);
+        seq
+    }
+
+    // Solução vizinha cuja FO já foi calculada por `fo`
+    pub fn aplica(&self, mov: Movimento, fo: i32) -> Solucao {
+        Solucao::com_fo(self.vizinho(mov), fo)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rand;
+
+    use self::rand::Rng;
+    use super::*;
+    use instancia::{Instancia, Tarefa, Temporizacao};
+    use objetivo::OBJETIVOS;
+    use solver::gera_rng;
+
+    const N: usize = 7;
+
+    // Instância aleatória, com datas de liberação e setups opcionais
+    fn instancia(semente: u64, liberacao: bool, setup: bool) -> Instancia {
+        let mut rng = gera_rng(semente);
+        let tarefas = (0..N)
+            .map(|_| {
+                Tarefa::completa(rng.gen_range(1, 10),
+                                 rng.gen_range(0, 40),
+                                 rng.gen_range(1, 4),
+                                 rng.gen_range(1, 4),
+                                 if liberacao { rng.gen_range(0, 20) } else { 0 })
+            })
+            .collect();
+        if !setup {
+            return Instancia::new(tarefas);
+        }
+        let setups = (0..N)
+            .map(|i| (0..N).map(|j| if i == j { 0 } else { rng.gen_range(0, 6) }).collect())
+            .collect();
+        Instancia::com_setup(tarefas, setups).unwrap()
+    }
+
+    // Todos os movimentos de cada tipo sobre N posições
+    fn todos_movimentos() -> Vec<Movimento> {
+        let mut movs = vec![];
+        for i in 0..N {
+            for j in 0..N {
+                movs.push(Movimento::Insercao(i, j));
+                movs.push(Movimento::Swap(i, j));
+                if i + 1 < N && j + 1 < N {
+                    movs.push(Movimento::SwapAdj(i, j));
+                }
+            }
+        }
+        for x in 0..N {
+            for y in x + 1..N {
+                for z in y..N {
+                    movs.push(Movimento::SwapXyz(x, y, z));
+                }
+            }
+        }
+        for i in 0..N {
+            for k in i + 1..N + 1 {
+                movs.push(Movimento::TwoOpt(i, k));
+            }
+        }
+        movs
+    }
+
+    #[test]
+    fn delta_igual_avaliacao_completa() {
+        let movs = todos_movimentos();
+        for semente in 0..4 {
+            for &(liberacao, setup) in &[(false, false), (true, false), (false, true), (true, true)] {
+                let inst = instancia(semente, liberacao, setup);
+                let mut seq: Sequencia = (0..N).collect();
+                gera_rng(semente).shuffle(&mut seq);
+
+                for &objetivo in OBJETIVOS.iter() {
+                    for &temporizacao in &[Temporizacao::Semiativa, Temporizacao::Otima] {
+                        let av = Avaliador::new(&inst, objetivo, temporizacao);
+                        let sol = Solucao::new(&av, seq.clone());
+                        let delta = Delta::new(&av, &sol);
+                        for &mov in &movs {
+                            assert_eq!(delta.fo(mov),
+                                       Solucao::new(&av, delta.vizinho(mov)).fo(),
+                                       "{:?} com {} ({:?}), semente {}, liberação {}, setup {}",
+                                       mov,
+                                       objetivo.nome(),
+                                       temporizacao,
+                                       semente,
+                                       liberacao,
+                                       setup);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}